@@ -1,5 +1,9 @@
-use cosmwasm_schema::write_api;
-use counting_contract::msg::{ExecMsg, InstantiateMsg, QueryMsg};
+use std::env::current_dir;
+
+use cosmwasm_schema::{export_schema, schema_for, write_api};
+use counting_contract::msg::{
+    DonateResponse, ExecMsg, InstantiateMsg, QueryMsg, ResetResponse, WithdrawResponse,
+};
 
 fn main() {
     write_api! {
@@ -7,4 +11,10 @@ fn main() {
       execute: ExecMsg,
       query: QueryMsg,
     }
+
+    // `Response::data` of execute messages, which `write_api!` does not cover
+    let out_dir = current_dir().unwrap().join("schema").join("raw");
+    export_schema(&schema_for!(DonateResponse), &out_dir);
+    export_schema(&schema_for!(WithdrawResponse), &out_dir);
+    export_schema(&schema_for!(ResetResponse), &out_dir);
 }
@@ -1,25 +1,68 @@
 use crate::error::ContractError;
-use crate::state::{ParentDonation, PARENT_DONATION};
+use crate::state::{
+    donors, DonorActivity, MemberGroup, ParentDonation, ACCESS_MODE, ALLOWLIST, BADGES, COUNTER,
+    DENYLIST, DONOR_ACTIVITY, GROUP, MERKLE_ROOT, MERKLE_VERIFIED, MILESTONES, PARENT_DONATION,
+    PAUSED, PENDING_BADGES, RATE_LIMIT, RESERVED, REWARD_RATE, REWARD_TOKEN, ROLES, SPLIT, STATS,
+    VESTING,
+};
 use crate::{
-    msg::InstantiateMsg,
+    msg::{AccessMode, Badge, InstantiateMsg, MilestoneAction, Role, VestingSchedule},
     state::{State, STATE},
 };
-use cosmwasm_std::{Addr, Coin, DepsMut, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Reply,
+    Response, StdResult, Storage, SubMsg, SubMsgResult, Timestamp, Uint128, WasmMsg,
+};
 use cw2::{get_contract_version, set_contract_version, ContractVersion};
+use cw20::MinterResponse;
+use cw4::{Cw4QueryMsg, MemberResponse};
 use cw_storage_plus::Item;
+use cw_utils::parse_reply_instantiate_data;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+pub const MAX_MEMO_LENGTH: usize = 256;
+/// Gas a single donation hook may use, so even a hook running out of gas cannot block donating
+const HOOK_GAS_LIMIT: u64 = 300_000;
+
+/// Counted donations a donor needs for the `TenDonations` badge
+const BADGE_DONATIONS: u64 = 10;
+/// Leaderboard positions earning the `TopContributor` badge
+const TOP_CONTRIBUTORS: usize = 10;
+
+/// Active matching pools a single counted donation is matched from
+const MAX_MATCHED_POOLS: usize = 10;
+/// Highest matching ratio a pool can be funded with
+const MAX_MATCH_RATIO: u128 = 10;
+
+/// Scheduled donations processed by a single `ProcessScheduled` by default and at most
+const DEFAULT_SCHEDULED_LIMIT: u32 = 10;
+const MAX_SCHEDULED_LIMIT: u32 = 30;
+
+pub const HOOK_REPLY_ID: u64 = 1;
+pub const REWARD_TOKEN_REPLY_ID: u64 = 2;
+/// Milestone messages reply with this plus the milestone counter
+pub const MILESTONE_REPLY_ID_BASE: u64 = 1 << 32;
+/// Badge mints reply with this plus the mint ID
+pub const BADGE_REPLY_ID_BASE: u64 = 1 << 63;
+
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    grant_all_roles(deps.storage, &info.sender)?;
+    COUNTER.save(deps.storage, &msg.counter, env.block.height)?;
     STATE.save(
         deps.storage,
         &State {
-            counter: msg.counter,
             minimal_donation: msg.minimal_donation,
-            owner: info.sender,
+            owner: info.sender.clone(),
             donating_parent: msg.parent.as_ref().map(|p| p.donating_period),
         },
     )?;
@@ -35,48 +78,650 @@ pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> Std
         )?;
     }
 
-    Ok(Response::new())
+    let mut resp = Response::new();
+    if let Some(token) = msg.reward_token {
+        REWARD_RATE.save(deps.storage, &token.rate)?;
+        let init = cw20_base::msg::InstantiateMsg {
+            name: token.name,
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: env.contract.address.to_string(),
+                cap: None,
+            }),
+            marketing: None,
+        };
+        let msg = WasmMsg::Instantiate {
+            admin: Some(info.sender.to_string()),
+            code_id: token.code_id,
+            msg: to_binary(&init)?,
+            funds: vec![],
+            label: format!("{} supporter token", token.symbol),
+        };
+        resp = resp.add_submessage(SubMsg::reply_on_success(msg, REWARD_TOKEN_REPLY_ID));
+    }
+
+    Ok(resp)
+}
+
+/// Badge token IDs are unique per donor, so each badge can be held only once.
+fn badge_token_id(badge: Badge, donor: &Addr) -> String {
+    format!("{badge}/{donor}")
+}
+
+fn grant_all_roles(storage: &mut dyn Storage, addr: &Addr) -> StdResult<()> {
+    for role in Role::ALL {
+        ROLES.save(storage, (role, addr), &Empty {})?;
+    }
+    Ok(())
+}
+
+fn ensure_role(storage: &dyn Storage, role: Role, addr: &Addr) -> Result<(), ContractError> {
+    if !ROLES.has(storage, (role, addr)) {
+        return Err(ContractError::MissingRole { role });
+    }
+    Ok(())
+}
+
+/// Like `ensure_role`, but members of a group granting roles also hold `Resetter` and
+/// `Withdrawer`.
+fn ensure_role_or_member(deps: Deps, role: Role, addr: &Addr) -> Result<(), ContractError> {
+    let err = match ensure_role(deps.storage, role, addr) {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+    if matches!(role, Role::Resetter | Role::Withdrawer) {
+        if let Some(group) = GROUP.may_load(deps.storage)? {
+            if group.grant_roles && group_member(deps, &group, addr)? {
+                return Ok(());
+            }
+        }
+    }
+    Err(err)
+}
+
+fn group_member(deps: Deps, group: &MemberGroup, addr: &Addr) -> StdResult<bool> {
+    let member: MemberResponse = deps.querier.query_wasm_smart(
+        &group.addr,
+        &Cw4QueryMsg::Member {
+            addr: addr.to_string(),
+            at_height: None,
+        },
+    )?;
+    Ok(member
+        .weight
+        .is_some_and(|weight| weight >= group.min_weight))
+}
+
+fn vested_fraction(vesting: &VestingSchedule, now: Timestamp) -> Decimal {
+    match *vesting {
+        VestingSchedule::Cliff { at } if now < at => Decimal::zero(),
+        VestingSchedule::Cliff { .. } => Decimal::one(),
+        VestingSchedule::Linear { start, .. } if now <= start => Decimal::zero(),
+        VestingSchedule::Linear { end, .. } if now >= end => Decimal::one(),
+        VestingSchedule::Linear { start, end } => {
+            Decimal::from_ratio(now.nanos() - start.nanos(), end.nanos() - start.nanos())
+        }
+    }
+}
+
+/// Contract balance without the reserved funds.
+fn available_balance(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let mut balance = deps.querier.query_all_balances(&env.contract.address)?;
+    let reserved = RESERVED.may_load(deps.storage)?.unwrap_or_default();
+    sub_coins(&mut balance, &reserved);
+    Ok(balance)
+}
+
+/// Splits the contract balance into funds which can be withdrawn and funds still locked by the
+/// vesting schedule. Everything donated so far, including what was already withdrawn, vests
+/// along the schedule.
+fn claimable_funds(deps: Deps, env: &Env) -> StdResult<(Vec<Coin>, Vec<Coin>)> {
+    let balance = available_balance(deps, env)?;
+    let vesting = match VESTING.may_load(deps.storage)? {
+        Some(vesting) => vesting,
+        None => return Ok((balance, vec![])),
+    };
+    let fraction = vested_fraction(&vesting, env.block.time);
+    let withdrawn = STATS.may_load(deps.storage)?.unwrap_or_default().withdrawn;
+
+    let mut claimable = vec![];
+    let mut locked = vec![];
+    for coin in balance {
+        let withdrawn = withdrawn
+            .iter()
+            .find(|withdrawn| withdrawn.denom == coin.denom)
+            .map_or(Uint128::zero(), |withdrawn| withdrawn.amount);
+        let vested = (coin.amount + withdrawn) * fraction;
+        let amount = vested.saturating_sub(withdrawn).min(coin.amount);
+
+        if !amount.is_zero() {
+            claimable.push(Coin::new(amount.u128(), &coin.denom));
+        }
+        if amount < coin.amount {
+            locked.push(Coin::new((coin.amount - amount).u128(), coin.denom));
+        }
+    }
+
+    Ok((claimable, locked))
+}
+
+fn record_withdrawn(storage: &mut dyn Storage, funds: &[Coin]) -> StdResult<()> {
+    let mut stats = STATS.may_load(storage)?.unwrap_or_default();
+    add_coins(&mut stats.withdrawn, funds);
+    STATS.save(storage, &stats)
+}
+
+/// Adds `funds` to per-denom `totals`, keeping them sorted by denom.
+fn add_coins(totals: &mut Vec<Coin>, funds: &[Coin]) {
+    for coin in funds.iter().filter(|coin| !coin.amount.is_zero()) {
+        match totals.iter_mut().find(|total| total.denom == coin.denom) {
+            Some(total) => total.amount += coin.amount,
+            None => {
+                totals.push(coin.clone());
+                totals.sort_by(|a, b| a.denom.cmp(&b.denom));
+            }
+        }
+    }
+}
+
+/// Holds `funds` out of the campaign until released.
+fn reserve(storage: &mut dyn Storage, funds: &[Coin]) -> StdResult<()> {
+    let mut reserved = RESERVED.may_load(storage)?.unwrap_or_default();
+    add_coins(&mut reserved, funds);
+    RESERVED.save(storage, &reserved)
+}
+
+/// Returns reserved funds to the campaign, or frees them to be sent out.
+fn release(storage: &mut dyn Storage, funds: &[Coin]) -> StdResult<()> {
+    let mut reserved = RESERVED.may_load(storage)?.unwrap_or_default();
+    sub_coins(&mut reserved, funds);
+    RESERVED.save(storage, &reserved)
+}
+
+/// Funds covering `repetitions` scheduled donations of `amount` plus `tip`.
+fn schedule_funds(amount: Uint128, tip: Uint128, repetitions: u32) -> StdResult<Uint128> {
+    Ok(amount
+        .checked_add(tip)?
+        .checked_mul(Uint128::from(repetitions))?)
+}
+
+/// Subtracts `funds` from `totals`, saturating at zero and dropping emptied denoms.
+fn sub_coins(totals: &mut Vec<Coin>, funds: &[Coin]) {
+    for coin in funds {
+        if let Some(total) = totals.iter_mut().find(|total| total.denom == coin.denom) {
+            total.amount = total.amount.saturating_sub(coin.amount);
+        }
+    }
+    totals.retain(|total| !total.amount.is_zero());
+}
+
+/// Sends withdrawn funds to the configured beneficiaries, or to the owner if no split is set.
+/// Every receiver gets a single message; amounts are rounded down and the dust goes to the
+/// residual receiver.
+fn payout(storage: &dyn Storage, owner: Addr, funds: Vec<Coin>) -> StdResult<Vec<BankMsg>> {
+    let split = match SPLIT.may_load(storage)? {
+        Some(split) => split,
+        None => {
+            return Ok(vec![BankMsg::Send {
+                to_address: owner.into(),
+                amount: funds,
+            }])
+        }
+    };
+
+    let mut payouts: Vec<(Addr, Vec<Coin>)> = split
+        .beneficiaries
+        .iter()
+        .map(|(addr, share)| {
+            let amount = funds
+                .iter()
+                .map(|coin| Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount * *share,
+                })
+                .collect();
+            (addr.clone(), amount)
+        })
+        .collect();
+
+    let dust: Vec<_> = funds
+        .iter()
+        .map(|coin| {
+            let paid: Uint128 = payouts
+                .iter()
+                .flat_map(|(_, amount)| amount)
+                .filter(|paid| paid.denom == coin.denom)
+                .map(|paid| paid.amount)
+                .sum();
+            Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount - paid,
+            }
+        })
+        .collect();
+    match payouts
+        .iter_mut()
+        .find(|(addr, _)| *addr == split.residual_receiver)
+    {
+        Some((_, amount)) => {
+            for (coin, dust) in amount.iter_mut().zip(dust) {
+                coin.amount += dust.amount;
+            }
+        }
+        None => payouts.push((split.residual_receiver, dust)),
+    }
+
+    let msgs = payouts
+        .into_iter()
+        .filter_map(|(addr, amount)| {
+            let amount: Vec<_> = amount
+                .into_iter()
+                .filter(|coin| !coin.amount.is_zero())
+                .collect();
+            (!amount.is_empty()).then(|| BankMsg::Send {
+                to_address: addr.into(),
+                amount,
+            })
+        })
+        .collect();
+
+    Ok(msgs)
+}
+
+pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+    match reply.id {
+        HOOK_REPLY_ID => Ok(hook_failed(reply.result)),
+        REWARD_TOKEN_REPLY_ID => reward_token_instantiated(deps, reply),
+        id if id >= BADGE_REPLY_ID_BASE => {
+            badge_minted(deps, id - BADGE_REPLY_ID_BASE, reply.result)
+        }
+        id if id >= MILESTONE_REPLY_ID_BASE => {
+            milestone_failed(deps, id - MILESTONE_REPLY_ID_BASE, reply.result)
+        }
+        id => Err(ContractError::UnknownReply { id }),
+    }
+}
+
+fn reward_token_instantiated(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(reply)?;
+    let addr = deps.api.addr_validate(&res.contract_address)?;
+    REWARD_TOKEN.save(deps.storage, &addr)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reward_token_instantiated")
+        .add_attribute("reward_token", addr.as_str()))
+}
+
+/// Hooks are dispatched with `reply_on_error`, so a failing hook only gets reported here while
+/// the donation itself goes through.
+fn hook_failed(result: SubMsgResult) -> Response {
+    let resp = Response::new().add_attribute("action", "hook_failed");
+    match result {
+        SubMsgResult::Err(err) => resp.add_attribute("error", err),
+        SubMsgResult::Ok(_) => resp,
+    }
+}
+
+/// Badge mints reply either way to clear their pending entry. A failed mint, e.g. after the
+/// collection minter changed, lets the donation through and leaves the badge unawarded, so a
+/// later donation tries again.
+fn badge_minted(
+    deps: DepsMut,
+    mint_id: u64,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let (donor, badge) = PENDING_BADGES.load(deps.storage, mint_id)?;
+    PENDING_BADGES.remove(deps.storage, mint_id);
+    match result {
+        SubMsgResult::Ok(_) => Ok(Response::new()),
+        SubMsgResult::Err(err) => {
+            BADGES.remove(deps.storage, (&donor, badge));
+            Ok(Response::new()
+                .add_attribute("action", "badge_mint_failed")
+                .add_attribute("donor", donor.as_str())
+                .add_attribute("badge", badge.as_str())
+                .add_attribute("error", err))
+        }
+    }
+}
+
+/// Milestone messages are dispatched with `reply_on_error` as well, so a failing target cannot
+/// keep the counter from passing the milestone. The funds sent along stay in the contract.
+fn milestone_failed(
+    deps: DepsMut,
+    counter: u64,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let mut milestone = MILESTONES.load(deps.storage, counter)?;
+    milestone.failed = true;
+    MILESTONES.save(deps.storage, counter, &milestone)?;
+    if let MilestoneAction::Execute { funds, .. } = &milestone.action {
+        let mut stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+        sub_coins(&mut stats.milestone_payouts, funds);
+        STATS.save(deps.storage, &stats)?;
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "milestone_failed")
+        .add_attribute("counter", counter.to_string());
+    match result {
+        SubMsgResult::Err(err) => Ok(resp.add_attribute("error", err)),
+        SubMsgResult::Ok(_) => Ok(resp),
+    }
+}
+
+/// Ensures `sender` is eligible under the published Merkle root, either verified before or by
+/// `proof`. Returns the root to cache the sender under when `proof` verified it.
+fn merkle_eligibility(
+    deps: Deps,
+    sender: &Addr,
+    proof: Option<&[String]>,
+) -> Result<Option<Binary>, ContractError> {
+    let root = match MERKLE_ROOT.may_load(deps.storage)? {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    if MERKLE_VERIFIED.has(deps.storage, (root.as_slice(), sender)) {
+        return Ok(None);
+    }
+
+    let leaf: [u8; 32] = Sha256::digest(sender.as_bytes()).into();
+    let hash = proof
+        .unwrap_or_default()
+        .iter()
+        .try_fold(leaf, |hash, sibling| {
+            let sibling = decode_hash(sibling)?;
+            let (first, second) = if hash <= sibling {
+                (hash, sibling)
+            } else {
+                (sibling, hash)
+            };
+            let mut hasher = Sha256::new();
+            hasher.update(first);
+            hasher.update(second);
+            Ok::<_, ContractError>(hasher.finalize().into())
+        })?;
+    if proof.is_none() || root.as_slice() != hash {
+        return Err(ContractError::NotEligible {
+            donor: sender.to_string(),
+        });
+    }
+    Ok(Some(root))
+}
+
+/// Enforces the per-donor rate limits on a donation of `funds` paid by `sender`, whether for
+/// itself or `on_behalf` of someone else. Returns the updated bookkeeping of `sender` to save,
+/// `None` if no limits are configured.
+fn rate_limit(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    sender: &Addr,
+    funds: &[Coin],
+    counted: bool,
+    on_behalf: bool,
+) -> Result<Option<DonorActivity>, ContractError> {
+    let limit = match RATE_LIMIT.may_load(deps.storage)? {
+        Some(limit) => limit,
+        None => return Ok(None),
+    };
+    let mut activity = DONOR_ACTIVITY
+        .may_load(deps.storage, sender)?
+        .unwrap_or_default();
+
+    if let Some(until) = activity.cooldown_ends {
+        if !until.is_expired(&env.block) {
+            return Err(ContractError::DonationCooldown { until });
+        }
+    }
+    activity.cooldown_ends = limit.cooldown.map(|cooldown| cooldown.after(&env.block));
+
+    if let Some(epoch) = limit.epoch.filter(|_| counted) {
+        let until = match activity.epoch_ends {
+            Some(until) if !until.is_expired(&env.block) => until,
+            _ => {
+                activity.epoch_counted = 0;
+                epoch.length.after(&env.block)
+            }
+        };
+        if activity.epoch_counted >= epoch.max_counted {
+            return Err(ContractError::EpochLimitReached {
+                max: epoch.max_counted,
+                until,
+            });
+        }
+        activity.epoch_ends = Some(until);
+        activity.epoch_counted += 1;
+    }
+
+    if let Some(cap) = limit.max_contribution {
+        let denom = &state.minimal_donation.denom;
+        let donated: Uint128 = funds
+            .iter()
+            .filter(|coin| &coin.denom == denom)
+            .map(|coin| coin.amount)
+            .sum();
+        let total = donors()
+            .may_load(deps.storage, sender)?
+            .map(|total| total.amount - total.funded_by_others)
+            .unwrap_or_default()
+            + activity.donated_for_others;
+        if total + donated > cap {
+            return Err(ContractError::ContributionCapExceeded {
+                cap: Coin {
+                    denom: denom.clone(),
+                    amount: cap,
+                },
+            });
+        }
+        if on_behalf {
+            activity.donated_for_others += donated;
+        }
+    }
+
+    Ok(Some(activity))
+}
+
+fn decode_hash(hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut decoded = [0; 32];
+    hex::decode_to_slice(hash, &mut decoded).map_err(|_| ContractError::InvalidHash {
+        hash: hash.to_owned(),
+    })?;
+    Ok(decoded)
+}
+
+/// Effect of a donation, shared by `exec::donate` and the `SimulateDonate` query.
+pub struct DonationOutcome {
+    pub counted: bool,
+    pub counter: u64,
+    /// Donations left until the next parent forward
+    pub donating_parent: Option<u64>,
+    pub forward: Option<(Addr, Vec<Coin>)>,
+    /// Merkle root the sender was verified against by a proof, to be cached
+    pub verified_root: Option<Binary>,
+    /// Rate limiting bookkeeping of the funder after the donation
+    pub activity: Option<DonorActivity>,
+}
+
+/// Ensures `addr` passes the access list, group membership and Merkle eligibility checks.
+/// Returns the root to cache `addr` under when `proof` verified it.
+fn ensure_eligible(
+    deps: Deps,
+    addr: &Addr,
+    proof: Option<&[String]>,
+) -> Result<Option<Binary>, ContractError> {
+    let allowed = match ACCESS_MODE
+        .may_load(deps.storage)?
+        .unwrap_or(AccessMode::Open)
+    {
+        AccessMode::Open => true,
+        AccessMode::Allowlist => ALLOWLIST.has(deps.storage, addr),
+        AccessMode::Denylist => !DENYLIST.has(deps.storage, addr),
+    };
+    if !allowed {
+        return Err(ContractError::DonorNotAllowed {
+            donor: addr.to_string(),
+        });
+    }
+    if let Some(group) = GROUP.may_load(deps.storage)? {
+        if !group_member(deps, &group, addr)? {
+            return Err(ContractError::NotGroupMember {
+                addr: addr.to_string(),
+            });
+        }
+    }
+    merkle_eligibility(deps, addr, proof)
+}
+
+/// Runs the acceptance logic of a donation of `funds` crediting `donor` and paid by `funder`,
+/// without writing anything. Both have to be eligible, `proof` being the one of `donor`, while
+/// the rate limits apply to `funder`. `received` tells whether `funds` are already part of the
+/// contract balance, as they are while executing, and `committed` are funds the transaction
+/// already sends out, which are still in the balance until it ends.
+#[allow(clippy::too_many_arguments)]
+fn evaluate_donation(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    donor: &Addr,
+    funder: &Addr,
+    funds: &[Coin],
+    proof: Option<&[String]>,
+    received: bool,
+    committed: &[Coin],
+) -> Result<DonationOutcome, ContractError> {
+    if PAUSED.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::Paused);
+    }
+    let verified_root = ensure_eligible(deps, donor, proof)?;
+    if funder != donor {
+        ensure_eligible(deps, funder, None)?;
+    }
+
+    let counted = state.minimal_donation.amount.is_zero()
+        || funds.iter().any(|coin| {
+            coin.denom == state.minimal_donation.denom
+                && coin.amount >= state.minimal_donation.amount
+        });
+    let mut outcome = DonationOutcome {
+        counted,
+        counter: COUNTER.load(deps.storage)?,
+        donating_parent: state.donating_parent,
+        forward: None,
+        verified_root,
+        activity: rate_limit(deps, env, state, funder, funds, counted, funder != donor)?,
+    };
+    if !outcome.counted {
+        return Ok(outcome);
+    }
+
+    outcome.counter += 1;
+    if let Some(parent) = &mut outcome.donating_parent {
+        *parent -= 1;
+        if *parent == 0 {
+            let parent_donation = PARENT_DONATION.load(deps.storage)?;
+            *parent = parent_donation.donating_parent_period;
+
+            let mut balance = available_balance(deps, env)?;
+            sub_coins(&mut balance, committed);
+            if !received {
+                add_coins(&mut balance, funds);
+            }
+            let funds = balance
+                .into_iter()
+                .map(|mut coin| {
+                    coin.amount = coin.amount * parent_donation.part;
+                    coin
+                })
+                .collect();
+            outcome.forward = Some((parent_donation.address, funds));
+        }
+    }
+
+    Ok(outcome)
 }
 
-pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
-    let ContractVersion { contract, version } = get_contract_version(deps.storage)?;
+/// Outcome of checking the stored contract version and storage layout against this code,
+/// without writing anything.
+pub struct MigrationPlan {
+    pub steps: Vec<String>,
+    pub state: State,
+    pub counter: u64,
+    pub owner_roles: bool,
+}
+
+pub fn migration_plan(storage: &dyn Storage) -> Result<MigrationPlan, ContractError> {
+    let ContractVersion { contract, version } = get_contract_version(storage)?;
     if contract != CONTRACT_NAME {
         return Err(ContractError::InvalidName(contract));
     }
-    let resp = match version.as_str() {
-        "0.1.4" => migrate_0_1(deps.branch()).map_err(ContractError::from)?,
-        "0.2.0" => migrate_0_2(deps.branch()).map_err(ContractError::from)?,
-        CONTRACT_VERSION => return Ok(Response::new()),
+    let mut steps = vec![];
+    let (state, counter) = match version.as_str() {
+        "0.1.4" => {
+            steps.push("0.1.4: merge `counter`, `minimal_donation` and `owner` items into `state`");
+            load_0_1(storage)?
+        }
+        "0.2.0" => {
+            steps.push("0.2.0: extend `state` with donating parent");
+            load_0_2(storage)?
+        }
+        "0.3.0" | "0.4.0" => load_0_4(storage)?,
+        CONTRACT_VERSION => (STATE.load(storage)?, COUNTER.load(storage)?),
         _ => return Err(ContractError::UnsupportedVersion(version)),
     };
 
+    let owner_roles = matches!(version.as_str(), "0.1.4" | "0.2.0" | "0.3.0");
+    if owner_roles {
+        steps.push("0.3.0: grant all roles to the owner");
+    }
+    if version != CONTRACT_VERSION {
+        steps.push("0.4.0: move `counter` out of `state` into snapshot storage");
+    }
+
+    Ok(MigrationPlan {
+        steps: steps.into_iter().map(String::from).collect(),
+        state,
+        counter,
+        owner_roles,
+    })
+}
+
+pub fn migrate(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let MigrationPlan {
+        steps,
+        state,
+        counter,
+        owner_roles,
+    } = migration_plan(deps.storage)?;
+    if steps.is_empty() {
+        return Ok(Response::new());
+    }
+
+    if owner_roles {
+        grant_all_roles(deps.storage, &state.owner)?;
+    }
+    STATE.save(deps.storage, &state)?;
+    COUNTER.save(deps.storage, &counter, env.block.height)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(resp)
+    Ok(Response::new())
 }
 
-pub fn migrate_0_1(deps: DepsMut) -> StdResult<Response> {
+fn load_0_1(storage: &dyn Storage) -> StdResult<(State, u64)> {
     const COUNTER: Item<u64> = Item::new("counter");
     const MINIMAL_DONATION: Item<Coin> = Item::new("minimal_donation");
     const OWNER: Item<Addr> = Item::new("owner");
 
-    let counter = COUNTER.load(deps.storage)?;
-    let minimal_donation = MINIMAL_DONATION.load(deps.storage)?;
-    let owner = OWNER.load(deps.storage)?;
-
-    STATE.save(
-        deps.storage,
-        &State {
-            counter,
-            minimal_donation,
-            owner,
-            donating_parent: None,
-        },
-    )?;
-
-    Ok(Response::new())
+    let state = State {
+        minimal_donation: MINIMAL_DONATION.load(storage)?,
+        owner: OWNER.load(storage)?,
+        donating_parent: None,
+    };
+    Ok((state, COUNTER.load(storage)?))
 }
 
-pub fn migrate_0_2(deps: DepsMut) -> StdResult<Response> {
+fn load_0_2(storage: &dyn Storage) -> StdResult<(State, u64)> {
     #[derive(Serialize, Deserialize)]
     struct OldState {
         counter: u64,
@@ -90,140 +735,2288 @@ pub fn migrate_0_2(deps: DepsMut) -> StdResult<Response> {
         counter,
         minimal_donation,
         owner,
-    } = OLD_STATE.load(deps.storage)?;
-
-    STATE.save(
-        deps.storage,
-        &State {
-            counter,
-            minimal_donation,
-            owner,
-            donating_parent: None,
-        },
-    )?;
+    } = OLD_STATE.load(storage)?;
 
-    Ok(Response::new())
+    let state = State {
+        minimal_donation,
+        owner,
+        donating_parent: None,
+    };
+    Ok((state, counter))
 }
 
-pub mod query {
-    use crate::{msg::ValueResponse, state::STATE};
-    use cosmwasm_std::{Deps, StdResult};
-
-    pub fn value(deps: Deps) -> StdResult<ValueResponse> {
-        let value = STATE.load(deps.storage)?.counter;
-        Ok(ValueResponse { value })
+fn load_0_4(storage: &dyn Storage) -> StdResult<(State, u64)> {
+    #[derive(Serialize, Deserialize)]
+    struct OldState {
+        counter: u64,
+        minimal_donation: Coin,
+        owner: Addr,
+        donating_parent: Option<u64>,
     }
+
+    const OLD_STATE: Item<OldState> = Item::new("state");
+
+    let OldState {
+        counter,
+        minimal_donation,
+        owner,
+        donating_parent,
+    } = OLD_STATE.load(storage)?;
+
+    let state = State {
+        minimal_donation,
+        owner,
+        donating_parent,
+    };
+    Ok((state, counter))
 }
 
-pub mod exec {
+pub mod query {
+    use super::{badge_token_id, claimable_funds, evaluate_donation, CONTRACT_VERSION};
     use crate::{
-        error::ContractError,
-        msg::ExecMsg,
-        state::{State, PARENT_DONATION, STATE},
+        msg::{
+            AccessListResponse, AccessMode, AccessModeResponse, BadgeResponse, BadgesResponse,
+            BeneficiaryResponse, DonationResponse, DonationsResponse, DonorResponse,
+            DonorStatusResponse, GroupResponse, HooksResponse, LargestDonationResponse,
+            LeaderboardResponse, MatchingPoolResponse, MatchingPoolsResponse, MerkleRootResponse,
+            MigrationPlanResponse, MilestoneResponse, MilestonesResponse, MultisigResponse,
+            ProposalResponse, ProposalStatus, ProposalsResponse, QueuedActionResponse,
+            QueuedActionsResponse, RateLimit, RewardTokenResponse, RoleGrant, RolesResponse,
+            ScheduleResponse, SchedulesResponse, SimulateDonateResponse, SplitResponse,
+            StateResponse, StatsResponse, TimelockResponse, ValueResponse, VestedResponse,
+        },
+        state::{
+            self, donors, DonationRecord, DonorTotal, WithdrawProposal, ACCESS_MODE, ALLOWLIST,
+            BADGES, BADGE_COLLECTION, COUNTER, DENYLIST, DONOR_ACTIVITY, GROUP, HOOKS,
+            MATCHING_POOLS, MERKLE_ROOT, MILESTONES, MULTISIG, PROPOSALS, PROPOSAL_APPROVALS,
+            QUEUED_ACTIONS, RATE_LIMIT, REWARD_RATE, REWARD_TOKEN, ROLES, SCHEDULES, SPLIT, STATE,
+            STATS, TIMELOCK_DELAY, VESTING,
+        },
     };
     use cosmwasm_std::{
-        to_binary, BankMsg, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+        Addr, Coin, Deps, Empty, Env, Order, StdError, StdResult, Storage, Timestamp, Uint128,
     };
+    use cw2::get_contract_version;
+    use cw_storage_plus::{Bound, Map};
 
-    pub fn reset(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-        let State {
-            counter: _,
-            minimal_donation,
-            donating_parent,
-            owner,
-        } = STATE.load(deps.storage)?;
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
 
-        if owner != info.sender {
-            return Err(ContractError::Unauthorized {
-                owner: owner.into(),
-            });
-        }
+    pub fn value(deps: Deps) -> StdResult<ValueResponse> {
+        let value = COUNTER.load(deps.storage)?;
+        Ok(ValueResponse { value })
+    }
 
-        STATE.save(
-            deps.storage,
-            &State {
-                counter: 0,
-                minimal_donation,
-                donating_parent,
-                owner,
-            },
-        )?;
-        Ok(Response::new()
-            .add_attribute("action", "reset")
-            .add_attribute("sender", info.sender.as_str()))
+    /// Tells what a donation of `funds` by `sender` would do if executed in the current block.
+    pub fn simulate_donate(
+        deps: Deps,
+        env: Env,
+        sender: String,
+        funds: Vec<Coin>,
+        proof: Option<Vec<String>>,
+    ) -> StdResult<SimulateDonateResponse> {
+        let sender = deps.api.addr_validate(&sender)?;
+        let state = STATE.load(deps.storage)?;
+        let outcome = evaluate_donation(
+            deps,
+            &env,
+            &state,
+            &sender,
+            &sender,
+            &funds,
+            proof.as_deref(),
+            false,
+            &[],
+        )
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+        Ok(SimulateDonateResponse {
+            counted: outcome.counted,
+            counter: outcome.counter,
+            parent_forward: outcome.forward.is_some(),
+            forwarded: outcome.forward.map(|(_, funds)| funds).unwrap_or_default(),
+        })
     }
 
-    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-        let mut state = STATE.load(deps.storage)?;
-        let mut resp = Response::new();
-        if state.minimal_donation.amount.is_zero()
-            || info.funds.iter().any(|coin| {
-                coin.denom == state.minimal_donation.denom
-                    && coin.amount >= state.minimal_donation.amount
-            })
-        {
-            state.counter += 1;
-            if let Some(parent) = &mut state.donating_parent {
-                *parent -= 1;
-                if *parent == 0 {
-                    let parent_donation = PARENT_DONATION.load(deps.storage)?;
-                    *parent = parent_donation.donating_parent_period;
-
-                    let funds: Vec<_> = deps
-                        .querier
-                        .query_all_balances(env.contract.address)?
-                        .into_iter()
-                        .map(|mut coin| {
-                            coin.amount = coin.amount * parent_donation.part;
-                            coin
-                        })
-                        .collect();
+    /// Counter value at the beginning of the block at `height`, before any of its transactions.
+    pub fn value_at(deps: Deps, height: u64) -> StdResult<Option<ValueResponse>> {
+        let value = COUNTER
+            .may_load_at_height(deps.storage, height)?
+            .map(|value| ValueResponse { value });
+        Ok(value)
+    }
 
-                    let msg = WasmMsg::Execute {
-                        contract_addr: parent_donation.address.to_string(),
-                        msg: to_binary(&ExecMsg::Donate {})?,
-                        funds,
-                    };
-                    resp = resp
-                        .add_message(msg)
-                        .add_attribute("donated_to_parent", parent_donation.address.to_string());
-                }
+    /// Takes raw storage so it can also be run against a dump of another contract's storage.
+    pub fn migration_plan(storage: &dyn Storage) -> MigrationPlanResponse {
+        let stored = get_contract_version(storage).ok();
+        let mut resp = MigrationPlanResponse {
+            contract: stored.as_ref().map(|v| v.contract.clone()),
+            from_version: stored.map(|v| v.version),
+            to_version: CONTRACT_VERSION.to_owned(),
+            steps: vec![],
+            state: None,
+            incompatibility: None,
+        };
+
+        match super::migration_plan(storage) {
+            Ok(plan) => {
+                resp.steps = plan.steps;
+                resp.state = Some(StateResponse {
+                    counter: plan.counter,
+                    minimal_donation: plan.state.minimal_donation,
+                    owner: plan.state.owner,
+                    donating_parent: plan.state.donating_parent,
+                });
             }
-            STATE.save(deps.storage, &state)?;
+            Err(err) => resp.incompatibility = Some(err.to_string()),
         }
-        // COUNTER.update(deps.storage, |counter| -> StdResult<_> { Ok(counter + 1) })?;
 
-        resp = resp
-            .add_attribute("action", "donate")
-            .add_attribute("sender", info.sender.as_str())
-            .add_attribute("counter", state.counter.to_string());
-        Ok(resp)
+        resp
     }
 
-    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-        let State {
-            counter: _,
-            minimal_donation: _,
-            donating_parent: _,
-            owner,
-        } = STATE.load(deps.storage)?;
-
-        if owner != info.sender {
-            return Err(ContractError::Unauthorized {
-                owner: owner.into(),
+    pub fn roles(deps: Deps) -> StdResult<RolesResponse> {
+        let roles = ROLES
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| key.map(|(role, addr)| RoleGrant { role, addr }))
+            .collect::<StdResult<_>>()?;
+        Ok(RolesResponse { roles })
+    }
+
+    pub fn multisig(deps: Deps) -> StdResult<Option<MultisigResponse>> {
+        let multisig = MULTISIG
+            .may_load(deps.storage)?
+            .map(|multisig| MultisigResponse {
+                approvers: multisig.approvers,
+                threshold: multisig.threshold,
+                proposal_ttl: multisig.proposal_ttl,
             });
-        }
+        Ok(multisig)
+    }
 
-        let funds = deps.querier.query_all_balances(&env.contract.address)?;
-        let bank_msg = BankMsg::Send {
-            to_address: owner.to_string(),
-            amount: funds,
-        };
-        let resp = Response::new()
-            .add_message(bank_msg)
-            .add_attribute("action", "withdraw")
-            .add_attribute("sender", info.sender.as_str());
+    pub fn proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse> {
+        let proposal = PROPOSALS.load(deps.storage, id)?;
+        proposal_response(deps, &env, id, proposal)
+    }
 
-        Ok(resp)
+    pub fn proposals(
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ProposalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let proposals = PROPOSALS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|proposal| {
+                let (id, proposal) = proposal?;
+                proposal_response(deps, &env, id, proposal)
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ProposalsResponse { proposals })
+    }
+
+    pub fn timelock(deps: Deps) -> StdResult<TimelockResponse> {
+        let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default();
+        Ok(TimelockResponse { delay })
+    }
+
+    pub fn queued_actions(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<QueuedActionsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let actions = QUEUED_ACTIONS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|queued| {
+                queued.map(|(id, queued)| QueuedActionResponse {
+                    id,
+                    action: queued.action,
+                    announcer: queued.announcer,
+                    execute_after: queued.execute_after,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(QueuedActionsResponse { actions })
+    }
+
+    pub fn vested(deps: Deps, env: Env) -> StdResult<VestedResponse> {
+        let (claimable, locked) = claimable_funds(deps, &env)?;
+        Ok(VestedResponse {
+            vesting: VESTING.may_load(deps.storage)?,
+            claimable,
+            locked,
+        })
+    }
+
+    pub fn split(deps: Deps) -> StdResult<Option<SplitResponse>> {
+        let split = SPLIT.may_load(deps.storage)?.map(|split| SplitResponse {
+            beneficiaries: split
+                .beneficiaries
+                .into_iter()
+                .map(|(addr, share)| BeneficiaryResponse { addr, share })
+                .collect(),
+            residual_receiver: split.residual_receiver,
+        });
+        Ok(split)
+    }
+
+    pub fn stats(deps: Deps) -> StdResult<StatsResponse> {
+        let stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+        Ok(StatsResponse {
+            counted_donations: stats.counted_donations,
+            uncounted_donations: stats.uncounted_donations,
+            unique_donors: stats.unique_donors,
+            received: stats.received,
+            withdrawn: stats.withdrawn,
+            forwarded: stats.forwarded,
+            largest_donation: stats
+                .largest_donation
+                .map(|largest| LargestDonationResponse {
+                    id: largest.id,
+                    donor: largest.donor,
+                    amount: largest.amount,
+                }),
+            parent_forwards: stats.parent_forwards,
+            matched: stats.matched,
+            milestone_payouts: stats.milestone_payouts,
+        })
+    }
+
+    pub fn donation(deps: Deps, id: u64) -> StdResult<DonationResponse> {
+        let donation = state::donations().load(deps.storage, id)?;
+        Ok(donation_response(id, donation))
+    }
+
+    /// Lists donations in `[from_time, to_time)`, optionally only those of a single donor.
+    /// Donations are ordered by time, so `start_after` continues right after the given donation.
+    pub fn donations(
+        deps: Deps,
+        donor: Option<String>,
+        from_time: Option<Timestamp>,
+        to_time: Option<Timestamp>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<DonationsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let history = state::donations();
+
+        let mut min = (from_time.map_or(0, |time| time.nanos()), 0);
+        if let Some(id) = start_after {
+            let donation = history.load(deps.storage, id)?;
+            min = min.max((donation.time.nanos(), id + 1));
+        }
+        let min = Some(Bound::inclusive(min));
+        let max = to_time.map(|time| Bound::exclusive((time.nanos(), 0)));
+
+        let range = match donor {
+            Some(donor) => {
+                let donor = deps.api.addr_validate(&donor)?;
+                history
+                    .idx
+                    .donor
+                    .sub_prefix(donor)
+                    .range(deps.storage, min, max, Order::Ascending)
+            }
+            None => history
+                .idx
+                .time
+                .range(deps.storage, min, max, Order::Ascending),
+        };
+        let donations = range
+            .take(limit)
+            .map(|donation| donation.map(|(id, donation)| donation_response(id, donation)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(DonationsResponse { donations })
+    }
+
+    fn donation_response(id: u64, donation: DonationRecord) -> DonationResponse {
+        DonationResponse {
+            id,
+            donor: donation.donor,
+            funds: donation.funds,
+            counted: donation.counted,
+            height: donation.height,
+            time: donation.time,
+            memo: donation.memo.filter(|_| !donation.memo_hidden),
+            memo_hidden: donation.memo_hidden,
+            funded_by: donation.funded_by,
+            matches: donation.matches,
+        }
+    }
+
+    pub fn leaderboard(
+        deps: Deps,
+        limit: Option<u32>,
+        start_after: Option<String>,
+    ) -> StdResult<LeaderboardResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let donors = donors();
+        let max = start_after
+            .as_ref()
+            .map(|addr| -> StdResult<_> {
+                let total = donors.load(deps.storage, addr)?;
+                Ok(Bound::exclusive((total.amount.u128(), addr)))
+            })
+            .transpose()?;
+
+        let donors = donors
+            .idx
+            .amount
+            .range(deps.storage, None, max, Order::Descending)
+            .take(limit)
+            .map(|donor| {
+                donor.map(|(addr, total)| DonorResponse {
+                    addr,
+                    amount: total.amount,
+                    donations: total.donations,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(LeaderboardResponse {
+            denom: STATE.load(deps.storage)?.minimal_donation.denom,
+            donors,
+        })
+    }
+
+    pub fn donor(deps: Deps, env: Env, addr: String) -> StdResult<DonorStatusResponse> {
+        let addr = deps.api.addr_validate(&addr)?;
+        let total = donors()
+            .may_load(deps.storage, &addr)?
+            .unwrap_or(DonorTotal {
+                amount: Uint128::zero(),
+                donations: 0,
+                counted: 0,
+                funded_by_others: Uint128::zero(),
+            });
+        let limit = RATE_LIMIT.may_load(deps.storage)?;
+        let limit = limit.as_ref();
+        let activity = DONOR_ACTIVITY
+            .may_load(deps.storage, &addr)?
+            .unwrap_or_default();
+
+        let cooldown_ends = activity
+            .cooldown_ends
+            .filter(|until| !until.is_expired(&env.block));
+        let epoch_ends = activity
+            .epoch_ends
+            .filter(|until| !until.is_expired(&env.block));
+        let epoch_remaining = limit.and_then(|limit| limit.epoch.as_ref()).map(|epoch| {
+            let counted = if epoch_ends.is_some() {
+                activity.epoch_counted
+            } else {
+                0
+            };
+            epoch.max_counted.saturating_sub(counted)
+        });
+        let contribution_remaining = limit.and_then(|limit| limit.max_contribution).map(|cap| {
+            cap.saturating_sub(total.amount - total.funded_by_others + activity.donated_for_others)
+        });
+
+        Ok(DonorStatusResponse {
+            addr,
+            amount: total.amount,
+            donations: total.donations,
+            cooldown_ends,
+            epoch_remaining,
+            epoch_ends,
+            contribution_remaining,
+        })
+    }
+
+    pub fn schedules(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<SchedulesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let schedules = SCHEDULES
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|schedule| {
+                schedule.map(|(id, schedule)| ScheduleResponse {
+                    id,
+                    donor: schedule.donor,
+                    amount: schedule.amount,
+                    tip: schedule.tip,
+                    interval: schedule.interval,
+                    remaining: schedule.remaining,
+                    next: schedule.next,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(SchedulesResponse { schedules })
+    }
+
+    pub fn matching_pools(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<MatchingPoolsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let pools = MATCHING_POOLS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|pool| {
+                pool.map(|(id, pool)| MatchingPoolResponse {
+                    id,
+                    sponsor: pool.sponsor,
+                    ratio: pool.ratio,
+                    cap: pool.cap,
+                    expires: pool.expires,
+                    remaining: pool.remaining,
+                    matched: pool.matched,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MatchingPoolsResponse { pools })
+    }
+
+    pub fn rate_limit(deps: Deps) -> StdResult<Option<RateLimit>> {
+        RATE_LIMIT.may_load(deps.storage)
+    }
+
+    pub fn reward_token(deps: Deps) -> StdResult<Option<RewardTokenResponse>> {
+        let address = match REWARD_TOKEN.may_load(deps.storage)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+        let rate = REWARD_RATE.load(deps.storage)?;
+        Ok(Some(RewardTokenResponse { address, rate }))
+    }
+
+    pub fn access_mode(deps: Deps) -> StdResult<AccessModeResponse> {
+        let mode = ACCESS_MODE
+            .may_load(deps.storage)?
+            .unwrap_or(AccessMode::Open);
+        Ok(AccessModeResponse { mode })
+    }
+
+    pub fn merkle_root(deps: Deps) -> StdResult<MerkleRootResponse> {
+        let root = MERKLE_ROOT
+            .may_load(deps.storage)?
+            .map(|root| hex::encode(root.as_slice()));
+        Ok(MerkleRootResponse { root })
+    }
+
+    pub fn group(deps: Deps) -> StdResult<Option<GroupResponse>> {
+        let group = GROUP.may_load(deps.storage)?.map(|group| GroupResponse {
+            addr: group.addr,
+            min_weight: group.min_weight,
+            grant_roles: group.grant_roles,
+        });
+        Ok(group)
+    }
+
+    pub fn allowlist(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AccessListResponse> {
+        access_list(deps, ALLOWLIST, start_after, limit)
+    }
+
+    pub fn denylist(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AccessListResponse> {
+        access_list(deps, DENYLIST, start_after, limit)
+    }
+
+    fn access_list(
+        deps: Deps,
+        list: Map<&Addr, Empty>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AccessListResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let addresses = list
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(AccessListResponse { addresses })
+    }
+
+    pub fn badge_collection(deps: Deps) -> StdResult<Option<Addr>> {
+        BADGE_COLLECTION.may_load(deps.storage)
+    }
+
+    pub fn badges(deps: Deps, donor: String) -> StdResult<BadgesResponse> {
+        let donor = deps.api.addr_validate(&donor)?;
+        let badges = BADGES
+            .prefix(&donor)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|badge| {
+                badge.map(|badge| BadgeResponse {
+                    badge,
+                    token_id: badge_token_id(badge, &donor),
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(BadgesResponse { badges })
+    }
+
+    pub fn hooks(deps: Deps) -> StdResult<HooksResponse> {
+        let hooks = HOOKS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        Ok(HooksResponse { hooks })
+    }
+
+    pub fn milestones(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<MilestonesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let milestones = MILESTONES
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|milestone| {
+                milestone.map(|(counter, milestone)| MilestoneResponse {
+                    counter,
+                    action: milestone.action,
+                    fired_height: milestone.fired_height,
+                    fired_time: milestone.fired_time,
+                    unfunded: milestone.unfunded,
+                    failed: milestone.failed,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(MilestonesResponse { milestones })
+    }
+
+    fn proposal_response(
+        deps: Deps,
+        env: &Env,
+        id: u64,
+        proposal: WithdrawProposal,
+    ) -> StdResult<ProposalResponse> {
+        let approvals = PROPOSAL_APPROVALS
+            .prefix(id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default();
+        let status = if proposal.executed {
+            ProposalStatus::Executed
+        } else if proposal.expires <= env.block.time {
+            ProposalStatus::Expired
+        } else {
+            ProposalStatus::Open
+        };
+
+        Ok(ProposalResponse {
+            id,
+            proposer: proposal.proposer,
+            amount: proposal.amount,
+            receiver: proposal.receiver,
+            expires: proposal.expires,
+            executed: proposal.executed,
+            status,
+            approvals,
+            executable_after: proposal
+                .approved_at
+                .map(|approved_at| approved_at.plus_seconds(delay)),
+        })
+    }
+}
+
+pub mod exec {
+    use super::{
+        add_coins, available_balance, badge_token_id, claimable_funds, decode_hash, ensure_role,
+        ensure_role_or_member, evaluate_donation, payout, record_withdrawn, release, reserve,
+        schedule_funds, sub_coins, DonationOutcome, BADGE_DONATIONS, BADGE_REPLY_ID_BASE,
+        DEFAULT_SCHEDULED_LIMIT, HOOK_GAS_LIMIT, HOOK_REPLY_ID, MAX_MATCHED_POOLS, MAX_MATCH_RATIO,
+        MAX_MEMO_LENGTH, MAX_SCHEDULED_LIMIT, MILESTONE_REPLY_ID_BASE, TOP_CONTRIBUTORS,
+    };
+    use crate::{
+        error::ContractError,
+        events::{
+            ConfigEvent, CountingEvent, DonateEvent, MilestoneEvent, ParentForwardEvent,
+            ResetEvent, WithdrawEvent,
+        },
+        msg::{
+            AccessMode, Badge, DonateResponse, DonationHookMsg, ExecMsg, Group, MilestoneAction,
+            Multisig, Parent, PoolMatch, RateLimit, ResetResponse, RewardRate, Role, Split,
+            VestingSchedule, WithdrawResponse,
+        },
+        soulbound,
+        state::{
+            donations, donors, DonationRecord, DonorTotal, LargestDonation, MatchingPool,
+            MemberGroup, Milestone, MultisigConfig, ParentDonation, PaymentSplit, QueuedAction,
+            Schedule, State, Stats, WithdrawProposal, ACCESS_MODE, ACTIVE_POOLS, ALLOWLIST, BADGES,
+            BADGE_COLLECTION, COUNTER, DENYLIST, DONOR_ACTIVITY, GROUP, HOOKS, MATCHING_POOLS,
+            MERKLE_ROOT, MERKLE_VERIFIED, MILESTONES, MULTISIG, NEXT_ACTION_ID, NEXT_BADGE_MINT,
+            NEXT_DONATION_ID, NEXT_POOL_ID, NEXT_PROPOSAL_ID, NEXT_SCHEDULE_ID, PARENT_DONATION,
+            PAUSED, PENDING_BADGES, PROPOSALS, PROPOSAL_APPROVALS, QUEUED_ACTIONS, RATE_LIMIT,
+            REWARD_RATE, REWARD_TOKEN, ROLES, SCHEDULES, SCHEDULE_CURSOR, SPLIT, STATE, STATS,
+            TIMELOCK_DELAY, VESTING,
+        },
+    };
+    use cosmwasm_std::{
+        to_binary, Addr, BankMsg, Binary, Coin, Decimal, DepsMut, Empty, Env, MessageInfo, Order,
+        Response, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    };
+    use cw2::query_contract_info;
+    use cw20::Cw20ExecuteMsg;
+    use cw721_base::{ExecuteMsg as Cw721ExecuteMsg, Extension};
+    use cw_storage_plus::{Bound, Map};
+    use cw_utils::{must_pay, Duration, Expiration};
+
+    pub fn reset(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        ensure_role_or_member(deps.as_ref(), Role::Resetter, &info.sender)?;
+
+        let previous = COUNTER.load(deps.storage)?;
+        COUNTER.save(deps.storage, &0, env.block.height)?;
+        let event = ResetEvent {
+            sender: info.sender.clone(),
+            previous,
+        };
+        Ok(Response::new()
+            .set_data(to_binary(&ResetResponse { previous })?)
+            .add_event(event.into_event())
+            .add_attribute("action", "reset")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn donate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        memo: Option<String>,
+        proof: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let donor = info.sender.clone();
+        donate_as(deps, env, info, donor, memo, proof, &mut vec![])
+    }
+
+    pub fn donate_for(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        beneficiary: String,
+        memo: Option<String>,
+        proof: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let beneficiary = deps.api.addr_validate(&beneficiary)?;
+        donate_as(deps, env, info, beneficiary, memo, proof, &mut vec![])
+    }
+
+    /// Donation of `info.funds` crediting `donor`, who is the sender unless donating on behalf
+    /// of someone. Both the sender and `donor` have to be eligible, while the rate limits only
+    /// apply to the sender, so donating for someone never uses up their own limits.
+    /// `committed` holds the funds the transaction already sends out and gets the parent forward
+    /// and milestone payment of this donation added.
+    fn donate_as(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        donor: Addr,
+        memo: Option<String>,
+        proof: Option<Vec<String>>,
+        committed: &mut Vec<Coin>,
+    ) -> Result<Response, ContractError> {
+        if memo
+            .as_ref()
+            .is_some_and(|memo| memo.chars().count() > MAX_MEMO_LENGTH)
+        {
+            return Err(ContractError::MemoTooLong {
+                max: MAX_MEMO_LENGTH,
+            });
+        }
+
+        let funded_by = (donor != info.sender).then(|| info.sender.clone());
+        let mut state = STATE.load(deps.storage)?;
+        let mut stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+        let mut resp = Response::new();
+        let DonationOutcome {
+            counted,
+            counter,
+            donating_parent,
+            forward,
+            verified_root,
+            activity,
+        } = evaluate_donation(
+            deps.as_ref(),
+            &env,
+            &state,
+            &donor,
+            &info.sender,
+            &info.funds,
+            proof.as_deref(),
+            true,
+            committed,
+        )?;
+        if let Some(activity) = activity {
+            DONOR_ACTIVITY.save(deps.storage, &info.sender, &activity)?;
+        }
+        if let Some(root) = verified_root {
+            MERKLE_VERIFIED.save(deps.storage, (root.as_slice(), &donor), &Empty {})?;
+        }
+        if counted {
+            COUNTER.save(deps.storage, &counter, env.block.height)?;
+            state.donating_parent = donating_parent;
+            STATE.save(deps.storage, &state)?;
+        }
+        let mut forwarded = vec![];
+        if let Some((parent, funds)) = forward {
+            add_coins(&mut stats.forwarded, &funds);
+            stats.parent_forwards += 1;
+
+            let msg = WasmMsg::Execute {
+                contract_addr: parent.to_string(),
+                msg: to_binary(&ExecMsg::Donate {
+                    memo: None,
+                    proof: None,
+                })?,
+                funds: funds.clone(),
+            };
+            let event = ParentForwardEvent {
+                parent: parent.clone(),
+                funds: funds.clone(),
+            };
+            resp = resp
+                .add_message(msg)
+                .add_attribute("donated_to_parent", parent.to_string())
+                .add_event(event.into_event());
+            add_coins(committed, &funds);
+            forwarded = funds;
+        }
+        // Milestones pay out of what is left after the parent forward
+        if counted {
+            resp = fire_milestone(deps.branch(), &env, counter, committed, &mut stats, resp)?;
+        }
+        // COUNTER.update(deps.storage, |counter| -> StdResult<_> { Ok(counter + 1) })?;
+
+        let campaign_amount: Uint128 = info
+            .funds
+            .iter()
+            .filter(|coin| coin.denom == state.minimal_donation.denom)
+            .map(|coin| coin.amount)
+            .sum();
+        if !campaign_amount.is_zero() {
+            let total = donors().update(deps.storage, &donor, |total| -> StdResult<_> {
+                let mut total = total.unwrap_or(DonorTotal {
+                    amount: Uint128::zero(),
+                    donations: 0,
+                    counted: 0,
+                    funded_by_others: Uint128::zero(),
+                });
+                total.amount += campaign_amount;
+                total.donations += 1;
+                total.counted += u64::from(counted);
+                if funded_by.is_some() {
+                    total.funded_by_others += campaign_amount;
+                }
+                Ok(total)
+            })?;
+            resp = award_badges(deps.storage, &donor, &total, resp)?;
+        }
+
+        if let Some(mint) = reward_mint(deps.storage, &donor, counted, campaign_amount)? {
+            resp = resp.add_message(mint);
+        }
+
+        let donation_id = NEXT_DONATION_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_DONATION_ID.save(deps.storage, &(donation_id + 1))?;
+
+        let matches = if counted {
+            allocate_matches(
+                deps.storage,
+                &env,
+                &state.minimal_donation.denom,
+                campaign_amount,
+            )?
+        } else {
+            vec![]
+        };
+        let matched: Vec<_> = matches.iter().map(|m| m.amount.clone()).collect();
+        add_coins(&mut stats.matched, &matched);
+
+        if counted {
+            stats.counted_donations += 1;
+        } else {
+            stats.uncounted_donations += 1;
+        }
+        add_coins(&mut stats.received, &info.funds);
+        let donations = donations();
+        let first_donation = donations
+            .idx
+            .donor
+            .sub_prefix(donor.clone())
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none();
+        if first_donation {
+            stats.unique_donors += 1;
+        }
+        let largest = !campaign_amount.is_zero()
+            && match &stats.largest_donation {
+                Some(largest) => {
+                    largest.amount.denom != state.minimal_donation.denom
+                        || largest.amount.amount < campaign_amount
+                }
+                None => true,
+            };
+        if largest {
+            stats.largest_donation = Some(LargestDonation {
+                id: donation_id,
+                donor: donor.clone(),
+                amount: Coin {
+                    denom: state.minimal_donation.denom.clone(),
+                    amount: campaign_amount,
+                },
+            });
+        }
+        STATS.save(deps.storage, &stats)?;
+
+        donations.save(
+            deps.storage,
+            donation_id,
+            &DonationRecord {
+                donor: donor.clone(),
+                funds: info.funds.clone(),
+                counted,
+                height: env.block.height,
+                time: env.block.time,
+                memo: memo.clone(),
+                memo_hidden: false,
+                funded_by: funded_by.clone(),
+                matches,
+            },
+        )?;
+
+        let hooks = HOOKS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        if !hooks.is_empty() {
+            let msg = DonationHookMsg {
+                donor: donor.clone(),
+                funds: info.funds.clone(),
+                counter,
+            }
+            .into_binary()?;
+            for hook in hooks {
+                let hook = WasmMsg::Execute {
+                    contract_addr: hook.into(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                };
+                resp = resp.add_submessage(
+                    SubMsg::reply_on_error(hook, HOOK_REPLY_ID).with_gas_limit(HOOK_GAS_LIMIT),
+                );
+            }
+        }
+
+        let data = DonateResponse {
+            counter,
+            counted,
+            donation_id,
+            forwarded,
+        };
+        resp = resp.set_data(to_binary(&data)?);
+        let event = DonateEvent {
+            donor: donor.clone(),
+            funds: info.funds,
+            counted,
+            counter,
+            donation_id,
+        };
+        resp = resp
+            .add_event(event.into_event())
+            .add_attribute("action", "donate")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string())
+            .add_attribute("donation_id", donation_id.to_string());
+        if funded_by.is_some() {
+            resp = resp.add_attribute("beneficiary", donor.as_str());
+        }
+        if let Some(memo) = memo {
+            resp = resp.add_attribute("memo", memo);
+        }
+        Ok(resp)
+    }
+
+    pub fn schedule_donation(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Coin,
+        interval: Duration,
+        repetitions: u32,
+        tip: Uint128,
+    ) -> Result<Response, ContractError> {
+        if amount.amount.is_zero() {
+            return Err(ContractError::EmptySchedule);
+        }
+        let expected = Coin {
+            amount: schedule_funds(amount.amount, tip, repetitions)?,
+            denom: amount.denom.clone(),
+        };
+        if expected.amount.is_zero() || must_pay(&info, &amount.denom)? != expected.amount {
+            return Err(ContractError::InvalidScheduleFunds { expected });
+        }
+
+        let id = NEXT_SCHEDULE_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_SCHEDULE_ID.save(deps.storage, &(id + 1))?;
+        let next = match interval {
+            Duration::Height(_) => Expiration::AtHeight(env.block.height),
+            Duration::Time(_) => Expiration::AtTime(env.block.time),
+        };
+        SCHEDULES.save(
+            deps.storage,
+            id,
+            &Schedule {
+                donor: info.sender.clone(),
+                amount,
+                tip,
+                interval,
+                remaining: repetitions,
+                next,
+            },
+        )?;
+        reserve(deps.storage, &[expected])?;
+
+        Ok(Response::new()
+            .add_attribute("action", "schedule_donation")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("schedule_id", id.to_string()))
+    }
+
+    pub fn cancel_schedule(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let schedule = SCHEDULES.load(deps.storage, id)?;
+        if schedule.donor != info.sender {
+            return Err(ContractError::NotScheduleDonor { id });
+        }
+
+        SCHEDULES.remove(deps.storage, id);
+        let refund = Coin {
+            amount: schedule_funds(schedule.amount.amount, schedule.tip, schedule.remaining)?,
+            denom: schedule.amount.denom,
+        };
+        release(deps.storage, std::slice::from_ref(&refund))?;
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![refund],
+            })
+            .add_attribute("action", "cancel_schedule")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("schedule_id", id.to_string()))
+    }
+
+    /// Looks at up to `limit` schedules, resuming after the last one looked at by the previous
+    /// call and wrapping around, and donates the due ones through `donate_as`, as if their donor
+    /// sent them. Donations the contract would currently reject are skipped and stay due.
+    pub fn process_scheduled(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let limit = limit
+            .unwrap_or(DEFAULT_SCHEDULED_LIMIT)
+            .min(MAX_SCHEDULED_LIMIT) as usize;
+        let cursor = SCHEDULE_CURSOR.may_load(deps.storage)?;
+        let mut schedules = SCHEDULES
+            .range(
+                deps.storage,
+                cursor.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        if let Some(cursor) = cursor {
+            let wrapped = SCHEDULES
+                .range(
+                    deps.storage,
+                    None,
+                    Some(Bound::inclusive(cursor)),
+                    Order::Ascending,
+                )
+                .take(limit - schedules.len())
+                .collect::<StdResult<Vec<_>>>()?;
+            schedules.extend(wrapped);
+        }
+        if let Some((id, _)) = schedules.last() {
+            SCHEDULE_CURSOR.save(deps.storage, id)?;
+        }
+
+        let mut resp = Response::new();
+        let mut tips = vec![];
+        // Forwards, milestone payments and tips of the donations processed so far are still in
+        // the balance until the crank ends
+        let mut committed = vec![];
+        let mut processed = 0;
+        for (id, mut schedule) in schedules {
+            if !schedule.next.is_expired(&env.block) {
+                continue;
+            }
+            let funds = vec![schedule.amount.clone()];
+            let state = STATE.load(deps.storage)?;
+            let accepted = evaluate_donation(
+                deps.as_ref(),
+                &env,
+                &state,
+                &schedule.donor,
+                &schedule.donor,
+                &funds,
+                None,
+                false,
+                &committed,
+            );
+            if accepted.is_err() {
+                resp = resp.add_attribute("skipped_schedule", id.to_string());
+                continue;
+            }
+
+            release(deps.storage, &funds)?;
+            let donation = MessageInfo {
+                sender: schedule.donor.clone(),
+                funds,
+            };
+            let donor = schedule.donor.clone();
+            let donated = donate_as(
+                deps.branch(),
+                env.clone(),
+                donation,
+                donor,
+                None,
+                None,
+                &mut committed,
+            )?;
+            resp = resp
+                .add_submessages(donated.messages)
+                .add_events(donated.events);
+
+            let tip = Coin {
+                denom: schedule.amount.denom.clone(),
+                amount: schedule.tip,
+            };
+            release(deps.storage, std::slice::from_ref(&tip))?;
+            add_coins(&mut committed, std::slice::from_ref(&tip));
+            add_coins(&mut tips, &[tip]);
+
+            schedule.remaining -= 1;
+            if schedule.remaining == 0 {
+                SCHEDULES.remove(deps.storage, id);
+            } else {
+                schedule.next = schedule.interval.after(&env.block);
+                SCHEDULES.save(deps.storage, id, &schedule)?;
+            }
+            processed += 1;
+        }
+
+        if !tips.is_empty() {
+            resp = resp.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: tips,
+            });
+        }
+        Ok(resp
+            .add_attribute("action", "process_scheduled")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("processed", processed.to_string()))
+    }
+
+    pub fn fund_matching_pool(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        ratio: Decimal,
+        cap: Uint128,
+        expires: Expiration,
+    ) -> Result<Response, ContractError> {
+        let max_ratio = Decimal::from_ratio(MAX_MATCH_RATIO, 1u128);
+        if ratio.is_zero() || ratio > max_ratio {
+            return Err(ContractError::InvalidMatchRatio { max: max_ratio });
+        }
+        if matches!(expires, Expiration::Never {}) || expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidPoolExpiration { expires });
+        }
+        let min = STATE.load(deps.storage)?.minimal_donation;
+        let amount = must_pay(&info, &min.denom)?;
+        if amount < min.amount {
+            return Err(ContractError::PoolTooSmall { min });
+        }
+        let denom = min.denom;
+
+        let id = NEXT_POOL_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_POOL_ID.save(deps.storage, &(id + 1))?;
+        let remaining = Coin { denom, amount };
+        MATCHING_POOLS.save(
+            deps.storage,
+            id,
+            &MatchingPool {
+                sponsor: info.sender.clone(),
+                ratio,
+                cap,
+                expires,
+                remaining: remaining.clone(),
+                matched: Uint128::zero(),
+            },
+        )?;
+        ACTIVE_POOLS.save(deps.storage, id, &Empty {})?;
+        reserve(deps.storage, &[remaining])?;
+
+        Ok(Response::new()
+            .add_attribute("action", "fund_matching_pool")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("pool_id", id.to_string()))
+    }
+
+    pub fn reclaim_matching_pool(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut pool = MATCHING_POOLS.load(deps.storage, id)?;
+        if pool.sponsor != info.sender {
+            return Err(ContractError::NotSponsor { id });
+        }
+        if !pool.expires.is_expired(&env.block) {
+            return Err(ContractError::PoolNotExpired {
+                id,
+                expires: pool.expires,
+            });
+        }
+
+        let reclaimed = pool.remaining.clone();
+        pool.remaining.amount = Uint128::zero();
+        MATCHING_POOLS.save(deps.storage, id, &pool)?;
+        ACTIVE_POOLS.remove(deps.storage, id);
+        release(deps.storage, std::slice::from_ref(&reclaimed))?;
+
+        let mut resp = Response::new()
+            .add_attribute("action", "reclaim_matching_pool")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("pool_id", id.to_string());
+        if !reclaimed.amount.is_zero() {
+            resp = resp.add_message(BankMsg::Send {
+                to_address: info.sender.into(),
+                amount: vec![reclaimed],
+            });
+        }
+        Ok(resp)
+    }
+
+    /// Matches a counted donation of `amount` from the oldest `MAX_MATCHED_POOLS` active pools,
+    /// moving the matched funds out of the reserve into the campaign. Pools found emptied or
+    /// expired are dropped from the active ones.
+    fn allocate_matches(
+        storage: &mut dyn Storage,
+        env: &Env,
+        denom: &str,
+        amount: Uint128,
+    ) -> StdResult<Vec<PoolMatch>> {
+        if amount.is_zero() {
+            return Ok(vec![]);
+        }
+        let pool_ids = ACTIVE_POOLS
+            .keys(storage, None, None, Order::Ascending)
+            .take(MAX_MATCHED_POOLS)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut matches = vec![];
+        for pool_id in pool_ids {
+            let mut pool = MATCHING_POOLS.load(storage, pool_id)?;
+            if pool.remaining.amount.is_zero() || pool.expires.is_expired(&env.block) {
+                ACTIVE_POOLS.remove(storage, pool_id);
+                continue;
+            }
+            if pool.remaining.denom != denom {
+                continue;
+            }
+            let matched = (amount * pool.ratio)
+                .min(pool.cap)
+                .min(pool.remaining.amount);
+            if matched.is_zero() {
+                continue;
+            }
+            pool.remaining.amount -= matched;
+            pool.matched += matched;
+            MATCHING_POOLS.save(storage, pool_id, &pool)?;
+            if pool.remaining.amount.is_zero() {
+                ACTIVE_POOLS.remove(storage, pool_id);
+            }
+            matches.push(PoolMatch {
+                pool_id,
+                amount: Coin {
+                    denom: denom.to_owned(),
+                    amount: matched,
+                },
+            });
+        }
+
+        let matched: Vec<_> = matches.iter().map(|m| m.amount.clone()).collect();
+        release(storage, &matched)?;
+        Ok(matches)
+    }
+
+    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        ensure_role_or_member(deps.as_ref(), Role::Withdrawer, &info.sender)?;
+        if MULTISIG.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::MultisigRequired);
+        }
+        let State { owner, .. } = STATE.load(deps.storage)?;
+
+        let (funds, _) = claimable_funds(deps.as_ref(), &env)?;
+        record_withdrawn(deps.storage, &funds)?;
+
+        let mut resp = Response::new().set_data(to_binary(&WithdrawResponse {
+            sent: funds.clone(),
+        })?);
+        if !funds.is_empty() {
+            resp = send_payouts(resp, &info.sender, payout(deps.storage, owner, funds)?);
+        }
+        resp = resp
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    /// Adds withdrawal payouts to `resp`, reporting each of them with a `WithdrawEvent`.
+    fn send_payouts(mut resp: Response, sender: &Addr, msgs: Vec<BankMsg>) -> Response {
+        for msg in msgs {
+            if let BankMsg::Send { to_address, amount } = &msg {
+                let event = WithdrawEvent {
+                    sender: sender.clone(),
+                    receiver: Addr::unchecked(to_address),
+                    amount: amount.clone(),
+                };
+                resp = resp.add_event(event.into_event());
+            }
+            resp = resp.add_message(msg);
+        }
+        resp
+    }
+
+    pub fn grant_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        addr: String,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        ROLES.save(deps.storage, (role, &addr), &Empty {})?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "roles").into_event())
+            .add_attribute("action", "grant_role")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("role", role.as_str())
+            .add_attribute("addr", addr.as_str()))
+    }
+
+    pub fn revoke_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        addr: String,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        if role == Role::Admin && ROLES.has(deps.storage, (role, &addr)) {
+            let admins = ROLES
+                .prefix(role)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .take(2)
+                .count();
+            if admins < 2 {
+                return Err(ContractError::LastAdmin);
+            }
+        }
+        ROLES.remove(deps.storage, (role, &addr));
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "roles").into_event())
+            .add_attribute("action", "revoke_role")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("role", role.as_str())
+            .add_attribute("addr", addr.as_str()))
+    }
+
+    pub fn update_minimal_donation(
+        deps: DepsMut,
+        info: MessageInfo,
+        minimal_donation: Coin,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        STATE.update(deps.storage, |state| -> Result<_, ContractError> {
+            Ok(State {
+                minimal_donation,
+                ..state
+            })
+        })?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "minimal_donation").into_event())
+            .add_attribute("action", "update_minimal_donation")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_parent(
+        deps: DepsMut,
+        info: MessageInfo,
+        parent: Option<Parent>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match &parent {
+            Some(parent) => PARENT_DONATION.save(
+                deps.storage,
+                &ParentDonation {
+                    address: deps.api.addr_validate(&parent.addr)?,
+                    donating_parent_period: parent.donating_period,
+                    part: parent.part,
+                },
+            )?,
+            None => PARENT_DONATION.remove(deps.storage),
+        }
+        STATE.update(deps.storage, |state| -> Result<_, ContractError> {
+            Ok(State {
+                donating_parent: parent.as_ref().map(|p| p.donating_period),
+                ..state
+            })
+        })?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "parent").into_event())
+            .add_attribute("action", "update_parent")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn set_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Pauser, &info.sender)?;
+
+        PAUSED.save(deps.storage, &paused)?;
+
+        let action = if paused { "pause" } else { "unpause" };
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "paused").into_event())
+            .add_attribute("action", action)
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_multisig(
+        deps: DepsMut,
+        info: MessageInfo,
+        multisig: Option<Multisig>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        match multisig {
+            Some(multisig) => {
+                let mut approvers = multisig
+                    .approvers
+                    .iter()
+                    .map(|addr| deps.api.addr_validate(addr))
+                    .collect::<StdResult<Vec<_>>>()?;
+                approvers.sort();
+                approvers.dedup();
+
+                if multisig.threshold == 0 || multisig.threshold as usize > approvers.len() {
+                    return Err(ContractError::InvalidThreshold {
+                        threshold: multisig.threshold,
+                        approvers: approvers.len() as u32,
+                    });
+                }
+
+                MULTISIG.save(
+                    deps.storage,
+                    &MultisigConfig {
+                        approvers,
+                        threshold: multisig.threshold,
+                        proposal_ttl: multisig.proposal_ttl,
+                    },
+                )?;
+            }
+            None => MULTISIG.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "multisig").into_event())
+            .add_attribute("action", "update_multisig")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    fn ensure_approver(
+        storage: &dyn Storage,
+        addr: &Addr,
+    ) -> Result<MultisigConfig, ContractError> {
+        let multisig = MULTISIG
+            .may_load(storage)?
+            .ok_or(ContractError::MultisigNotConfigured)?;
+        if !multisig.approvers.contains(addr) {
+            return Err(ContractError::NotApprover {
+                addr: addr.to_string(),
+            });
+        }
+        Ok(multisig)
+    }
+
+    fn load_open_proposal(
+        storage: &dyn Storage,
+        env: &Env,
+        id: u64,
+    ) -> Result<WithdrawProposal, ContractError> {
+        let proposal = PROPOSALS.load(storage, id)?;
+        if proposal.executed {
+            return Err(ContractError::ProposalExecuted { id });
+        }
+        if proposal.expires <= env.block.time {
+            return Err(ContractError::ProposalExpired { id });
+        }
+        Ok(proposal)
+    }
+
+    fn count_approvals(
+        storage: &dyn Storage,
+        multisig: &MultisigConfig,
+        id: u64,
+    ) -> StdResult<u32> {
+        // approvals of addresses removed from the approver set no longer count
+        let approvals = PROPOSAL_APPROVALS
+            .prefix(id)
+            .keys(storage, None, None, Order::Ascending)
+            .filter(|approver| {
+                approver
+                    .as_ref()
+                    .map_or(true, |approver| multisig.approvers.contains(approver))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(approvals.len() as u32)
+    }
+
+    /// Starts the timelock delay of a proposal once it reaches the approval threshold. The
+    /// proposal is kept open for `proposal_ttl` after the delay elapses.
+    fn record_threshold(
+        storage: &mut dyn Storage,
+        env: &Env,
+        multisig: &MultisigConfig,
+        id: u64,
+        proposal: &mut WithdrawProposal,
+    ) -> StdResult<()> {
+        if proposal.approved_at.is_some()
+            || count_approvals(storage, multisig, id)? < multisig.threshold
+        {
+            return Ok(());
+        }
+
+        let delay = TIMELOCK_DELAY.may_load(storage)?.unwrap_or_default();
+        let executable_until = env
+            .block
+            .time
+            .plus_seconds(delay)
+            .plus_seconds(multisig.proposal_ttl);
+        proposal.approved_at = Some(env.block.time);
+        proposal.expires = proposal.expires.max(executable_until);
+        PROPOSALS.save(storage, id, proposal)
+    }
+
+    pub fn propose_withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Vec<Coin>,
+        receiver: String,
+    ) -> Result<Response, ContractError> {
+        let multisig = ensure_approver(deps.storage, &info.sender)?;
+        // one coin per denom, so the vesting check sees the totals actually sent
+        let amount = {
+            let mut total = vec![];
+            add_coins(&mut total, &amount);
+            total
+        };
+        if amount.is_empty() {
+            return Err(ContractError::EmptyWithdraw);
+        }
+
+        let id = NEXT_PROPOSAL_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_PROPOSAL_ID.save(deps.storage, &(id + 1))?;
+
+        let receiver = deps.api.addr_validate(&receiver)?;
+        let mut proposal = WithdrawProposal {
+            proposer: info.sender.clone(),
+            amount,
+            receiver: receiver.clone(),
+            expires: env.block.time.plus_seconds(multisig.proposal_ttl),
+            executed: false,
+            approved_at: None,
+        };
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+        PROPOSAL_APPROVALS.save(deps.storage, (id, &info.sender), &Empty {})?;
+        record_threshold(deps.storage, &env, &multisig, id, &mut proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string())
+            .add_attribute("receiver", receiver.as_str()))
+    }
+
+    pub fn approve_withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let multisig = ensure_approver(deps.storage, &info.sender)?;
+        let mut proposal = load_open_proposal(deps.storage, &env, id)?;
+
+        if PROPOSAL_APPROVALS.has(deps.storage, (id, &info.sender)) {
+            return Err(ContractError::AlreadyApproved {
+                id,
+                addr: info.sender.into(),
+            });
+        }
+        PROPOSAL_APPROVALS.save(deps.storage, (id, &info.sender), &Empty {})?;
+        record_threshold(deps.storage, &env, &multisig, id, &mut proposal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "approve_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string()))
+    }
+
+    pub fn execute_withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let multisig = ensure_approver(deps.storage, &info.sender)?;
+        let mut proposal = load_open_proposal(deps.storage, &env, id)?;
+
+        let approvals = count_approvals(deps.storage, &multisig, id)?;
+        if approvals < multisig.threshold {
+            return Err(ContractError::NotEnoughApprovals {
+                id,
+                approvals,
+                threshold: multisig.threshold,
+            });
+        }
+        // Withdrawals wait out the timelock delay counted from reaching the threshold
+        let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default();
+        let execute_after = proposal
+            .approved_at
+            .unwrap_or(env.block.time)
+            .plus_seconds(delay);
+        if execute_after > env.block.time {
+            return Err(ContractError::WithdrawTimelocked { id, execute_after });
+        }
+
+        let mut requested = vec![];
+        add_coins(&mut requested, &proposal.amount);
+        let (claimable, _) = claimable_funds(deps.as_ref(), &env)?;
+        for requested in &requested {
+            let available = claimable
+                .iter()
+                .find(|coin| coin.denom == requested.denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            if requested.amount > available {
+                return Err(ContractError::NotVested {
+                    requested: requested.clone(),
+                    claimable: Coin {
+                        denom: requested.denom.clone(),
+                        amount: available,
+                    },
+                });
+            }
+        }
+        record_withdrawn(deps.storage, &proposal.amount)?;
+
+        proposal.executed = true;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+
+        let data = WithdrawResponse {
+            sent: proposal.amount.clone(),
+        };
+        let bank_msg = BankMsg::Send {
+            to_address: proposal.receiver.to_string(),
+            amount: proposal.amount,
+        };
+        Ok(send_payouts(Response::new(), &info.sender, vec![bank_msg])
+            .set_data(to_binary(&data)?)
+            .add_attribute("action", "execute_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string()))
+    }
+
+    /// Role required to queue `msg`, or `None` if it is not subject to the timelock.
+    pub fn timelocked_role(msg: &ExecMsg) -> Option<Role> {
+        use ExecMsg::*;
+
+        match msg {
+            Reset { .. } => Some(Role::Resetter),
+            Withdraw {} => Some(Role::Withdrawer),
+            UpdateMinimalDonation { .. }
+            | UpdateParent { .. }
+            | UpdateVesting { .. }
+            | UpdateSplit { .. }
+            | AddMilestone { .. }
+            | RemoveMilestone { .. }
+            | AddHook { .. }
+            | RemoveHook { .. }
+            | UpdateRewardRate { .. }
+            | UpdateBadgeCollection { .. }
+            | UpdateAccessMode { .. }
+            | UpdateMerkleRoot { .. }
+            | UpdateRateLimit { .. }
+            | UpdateAllowlist { .. }
+            | UpdateDenylist { .. } => Some(Role::ConfigManager),
+            UpdateMultisig { .. } | UpdateTimelock { .. } | UpdateGroup { .. } => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn ensure_not_timelocked(
+        storage: &dyn Storage,
+        msg: &ExecMsg,
+    ) -> Result<(), ContractError> {
+        if timelocked_role(msg).is_some()
+            && TIMELOCK_DELAY.may_load(storage)?.unwrap_or_default() > 0
+        {
+            return Err(ContractError::TimelockRequired);
+        }
+        Ok(())
+    }
+
+    pub fn update_timelock(
+        deps: DepsMut,
+        info: MessageInfo,
+        delay: u64,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        TIMELOCK_DELAY.save(deps.storage, &delay)?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "timelock").into_event())
+            .add_attribute("action", "update_timelock")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("delay", delay.to_string()))
+    }
+
+    pub fn queue_action(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: ExecMsg,
+    ) -> Result<Response, ContractError> {
+        let role = timelocked_role(&action).ok_or(ContractError::NotTimelocked)?;
+        ensure_role_or_member(deps.as_ref(), role, &info.sender)?;
+
+        let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default();
+        let execute_after = env.block.time.plus_seconds(delay);
+
+        let id = NEXT_ACTION_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_ACTION_ID.save(deps.storage, &(id + 1))?;
+        QUEUED_ACTIONS.save(
+            deps.storage,
+            id,
+            &QueuedAction {
+                action,
+                announcer: info.sender.clone(),
+                execute_after,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "queue_action")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("queued_action_id", id.to_string())
+            .add_attribute("execute_after", execute_after.to_string()))
+    }
+
+    pub fn cancel_action(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let queued = QUEUED_ACTIONS.load(deps.storage, id)?;
+        if queued.announcer != info.sender {
+            ensure_role(deps.storage, Role::Admin, &info.sender)?;
+        }
+
+        QUEUED_ACTIONS.remove(deps.storage, id);
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_action")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("queued_action_id", id.to_string()))
+    }
+
+    /// Removes an action whose delay has elapsed from the queue, so it can be dispatched. The
+    /// role check is left to the handler of the action itself.
+    pub fn take_queued_action(deps: DepsMut, env: &Env, id: u64) -> Result<ExecMsg, ContractError> {
+        let queued = QUEUED_ACTIONS.load(deps.storage, id)?;
+        if queued.execute_after > env.block.time {
+            return Err(ContractError::TimelockNotElapsed {
+                id,
+                execute_after: queued.execute_after,
+            });
+        }
+
+        QUEUED_ACTIONS.remove(deps.storage, id);
+        Ok(queued.action)
+    }
+
+    pub fn update_vesting(
+        deps: DepsMut,
+        info: MessageInfo,
+        vesting: Option<VestingSchedule>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match vesting {
+            Some(VestingSchedule::Linear { start, end }) if end <= start => {
+                return Err(ContractError::InvalidVesting)
+            }
+            Some(vesting) => VESTING.save(deps.storage, &vesting)?,
+            None => VESTING.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "vesting").into_event())
+            .add_attribute("action", "update_vesting")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_split(
+        deps: DepsMut,
+        info: MessageInfo,
+        split: Option<Split>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match split {
+            Some(split) => {
+                let total: Decimal = split.beneficiaries.iter().map(|b| b.share).sum();
+                if total != Decimal::one() {
+                    return Err(ContractError::InvalidShares { total });
+                }
+
+                let beneficiaries = split
+                    .beneficiaries
+                    .iter()
+                    .map(|b| Ok((deps.api.addr_validate(&b.addr)?, b.share)))
+                    .collect::<StdResult<_>>()?;
+                SPLIT.save(
+                    deps.storage,
+                    &PaymentSplit {
+                        beneficiaries,
+                        residual_receiver: deps.api.addr_validate(&split.residual_receiver)?,
+                    },
+                )?;
+            }
+            None => SPLIT.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "split").into_event())
+            .add_attribute("action", "update_split")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn moderate_memo(
+        deps: DepsMut,
+        info: MessageInfo,
+        donation_id: u64,
+        hidden: bool,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        let donations = donations();
+        let mut donation = donations.load(deps.storage, donation_id)?;
+        if donation.memo.is_none() {
+            return Err(ContractError::NoMemo { id: donation_id });
+        }
+        donation.memo_hidden = hidden;
+        donations.save(deps.storage, donation_id, &donation)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "moderate_memo")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("donation_id", donation_id.to_string())
+            .add_attribute("hidden", hidden.to_string()))
+    }
+
+    pub fn add_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        addr: String,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        HOOKS.save(deps.storage, &addr, &Empty {})?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "hooks").into_event())
+            .add_attribute("action", "add_hook")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("hook", addr.as_str()))
+    }
+
+    pub fn remove_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        addr: String,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        let addr = deps.api.addr_validate(&addr)?;
+        HOOKS.remove(deps.storage, &addr);
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "hooks").into_event())
+            .add_attribute("action", "remove_hook")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("hook", addr.as_str()))
+    }
+
+    pub fn add_milestone(
+        deps: DepsMut,
+        info: MessageInfo,
+        counter: u64,
+        action: MilestoneAction,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        let current = COUNTER.load(deps.storage)?;
+        if counter <= current {
+            return Err(ContractError::MilestoneReached { counter, current });
+        }
+        match &action {
+            MilestoneAction::Event {} => (),
+            MilestoneAction::BankPayment { to_address, .. } => {
+                deps.api.addr_validate(to_address)?;
+            }
+            MilestoneAction::Execute { contract_addr, .. } => {
+                deps.api.addr_validate(contract_addr)?;
+            }
+        }
+        MILESTONES.save(
+            deps.storage,
+            counter,
+            &Milestone {
+                action,
+                fired_height: None,
+                fired_time: None,
+                unfunded: false,
+                failed: false,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "milestones").into_event())
+            .add_attribute("action", "add_milestone")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string()))
+    }
+
+    pub fn remove_milestone(
+        deps: DepsMut,
+        info: MessageInfo,
+        counter: u64,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        MILESTONES.remove(deps.storage, counter);
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "milestones").into_event())
+            .add_attribute("action", "remove_milestone")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string()))
+    }
+
+    pub fn update_reward_rate(
+        deps: DepsMut,
+        info: MessageInfo,
+        rate: RewardRate,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        if REWARD_TOKEN.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::NoRewardToken);
+        }
+        REWARD_RATE.save(deps.storage, &rate)?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "reward_rate").into_event())
+            .add_attribute("action", "update_reward_rate")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_badge_collection(
+        deps: DepsMut,
+        info: MessageInfo,
+        collection: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match collection {
+            Some(collection) => {
+                let collection = deps.api.addr_validate(&collection)?;
+                let info = query_contract_info(&deps.querier, &collection)?;
+                if info.contract != soulbound::CONTRACT_NAME {
+                    return Err(ContractError::TransferableBadges {
+                        collection: collection.into(),
+                    });
+                }
+                BADGE_COLLECTION.save(deps.storage, &collection)?;
+            }
+            None => BADGE_COLLECTION.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "badge_collection").into_event())
+            .add_attribute("action", "update_badge_collection")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_access_mode(
+        deps: DepsMut,
+        info: MessageInfo,
+        mode: AccessMode,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        ACCESS_MODE.save(deps.storage, &mode)?;
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "access_mode").into_event())
+            .add_attribute("action", "update_access_mode")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_merkle_root(
+        deps: DepsMut,
+        info: MessageInfo,
+        root: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match root {
+            Some(root) => {
+                let root = decode_hash(&root)?;
+                MERKLE_ROOT.save(deps.storage, &Binary::from(root.as_slice()))?;
+            }
+            None => MERKLE_ROOT.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "merkle_root").into_event())
+            .add_attribute("action", "update_merkle_root")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    /// Admin only, as the group may grant the `Resetter` and `Withdrawer` roles to its members.
+    pub fn update_group(
+        deps: DepsMut,
+        info: MessageInfo,
+        group: Option<Group>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::Admin, &info.sender)?;
+
+        match group {
+            Some(group) => {
+                let group = MemberGroup {
+                    addr: deps.api.addr_validate(&group.addr)?,
+                    min_weight: group.min_weight,
+                    grant_roles: group.grant_roles,
+                };
+                GROUP.save(deps.storage, &group)?;
+            }
+            None => GROUP.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "group").into_event())
+            .add_attribute("action", "update_group")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_rate_limit(
+        deps: DepsMut,
+        info: MessageInfo,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        match rate_limit {
+            Some(rate_limit) => RATE_LIMIT.save(deps.storage, &rate_limit)?,
+            None => RATE_LIMIT.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, "rate_limit").into_event())
+            .add_attribute("action", "update_rate_limit")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn update_allowlist(
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        update_access_list(deps, info, ALLOWLIST, "allowlist", add, remove)
+    }
+
+    pub fn update_denylist(
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        update_access_list(deps, info, DENYLIST, "denylist", add, remove)
+    }
+
+    /// Adds first, so an address both added and removed ends up removed.
+    fn update_access_list(
+        deps: DepsMut,
+        info: MessageInfo,
+        list: Map<&Addr, Empty>,
+        name: &str,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.storage, Role::ConfigManager, &info.sender)?;
+
+        for addr in &add {
+            let addr = deps.api.addr_validate(addr)?;
+            list.save(deps.storage, &addr, &Empty {})?;
+        }
+        for addr in &remove {
+            let addr = deps.api.addr_validate(addr)?;
+            list.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_event(ConfigEvent::new(&info.sender, name).into_event())
+            .add_attribute("action", format!("update_{name}"))
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("added", add.len().to_string())
+            .add_attribute("removed", remove.len().to_string()))
+    }
+
+    /// Mints every badge `donor` earned with the contribution `total` and does not hold yet.
+    fn award_badges(
+        storage: &mut dyn Storage,
+        donor: &Addr,
+        total: &DonorTotal,
+        mut resp: Response,
+    ) -> StdResult<Response> {
+        let collection = match BADGE_COLLECTION.may_load(storage)? {
+            Some(collection) => collection,
+            None => return Ok(resp),
+        };
+        let top_contributors = donors()
+            .idx
+            .amount
+            .range(storage, None, None, Order::Descending)
+            .take(TOP_CONTRIBUTORS)
+            .map(|donor| donor.map(|(addr, _)| addr))
+            .collect::<StdResult<Vec<_>>>()?;
+        let earned = [
+            (Badge::FirstDonation, total.counted >= 1),
+            (Badge::TenDonations, total.counted >= BADGE_DONATIONS),
+            (Badge::TopContributor, top_contributors.contains(donor)),
+        ];
+
+        for (badge, earned) in earned {
+            if !earned || BADGES.has(storage, (donor, badge)) {
+                continue;
+            }
+            BADGES.save(storage, (donor, badge), &Empty {})?;
+            let mint_id = NEXT_BADGE_MINT.may_load(storage)?.unwrap_or_default();
+            NEXT_BADGE_MINT.save(storage, &(mint_id + 1))?;
+            PENDING_BADGES.save(storage, mint_id, &(donor.clone(), badge))?;
+
+            let mint = Cw721ExecuteMsg::<Extension, Empty>::Mint {
+                token_id: badge_token_id(badge, donor),
+                owner: donor.to_string(),
+                token_uri: None,
+                extension: None,
+            };
+            let mint = WasmMsg::Execute {
+                contract_addr: collection.to_string(),
+                msg: to_binary(&mint)?,
+                funds: vec![],
+            };
+            resp = resp
+                .add_submessage(SubMsg::reply_always(mint, BADGE_REPLY_ID_BASE + mint_id))
+                .add_attribute("badge", badge.as_str());
+        }
+        Ok(resp)
+    }
+
+    /// Mint of supporter tokens earned by `donor`, `None` without a reward token or if nothing
+    /// is earned.
+    fn reward_mint(
+        storage: &dyn Storage,
+        donor: &Addr,
+        counted: bool,
+        campaign_amount: Uint128,
+    ) -> StdResult<Option<WasmMsg>> {
+        let token = match REWARD_TOKEN.may_load(storage)? {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+        let amount = match REWARD_RATE.load(storage)? {
+            RewardRate::PerDonation { amount } if counted => amount,
+            RewardRate::PerDonation { .. } => Uint128::zero(),
+            RewardRate::PerUnit { rate } => campaign_amount * rate,
+        };
+        if amount.is_zero() {
+            return Ok(None);
+        }
+
+        Ok(Some(WasmMsg::Execute {
+            contract_addr: token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: donor.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }))
+    }
+
+    /// Fires the milestone set at `counter` unless it already fired before a reset. Payments
+    /// are only made from the available balance less `committed`, which gets the payment added;
+    /// a milestone which cannot be funded still fires, but is marked unfunded instead of failing
+    /// the donation.
+    fn fire_milestone(
+        deps: DepsMut,
+        env: &Env,
+        counter: u64,
+        committed: &mut Vec<Coin>,
+        stats: &mut Stats,
+        mut resp: Response,
+    ) -> StdResult<Response> {
+        let mut milestone = match MILESTONES.may_load(deps.storage, counter)? {
+            Some(milestone) if milestone.fired_height.is_none() => milestone,
+            _ => return Ok(resp),
+        };
+        milestone.fired_height = Some(env.block.height);
+        milestone.fired_time = Some(env.block.time);
+
+        let mut payment = vec![];
+        match &milestone.action {
+            MilestoneAction::Event {} => (),
+            MilestoneAction::BankPayment { amount, .. } => add_coins(&mut payment, amount),
+            MilestoneAction::Execute { funds, .. } => add_coins(&mut payment, funds),
+        }
+        let mut available = available_balance(deps.as_ref(), env)?;
+        sub_coins(&mut available, committed);
+        milestone.unfunded = payment.iter().any(|coin| {
+            !available
+                .iter()
+                .any(|available| available.denom == coin.denom && available.amount >= coin.amount)
+        });
+        MILESTONES.save(deps.storage, counter, &milestone)?;
+
+        if milestone.unfunded {
+            payment.clear();
+        } else {
+            add_coins(&mut stats.milestone_payouts, &payment);
+            add_coins(committed, &payment);
+            match milestone.action {
+                MilestoneAction::Event {} => (),
+                MilestoneAction::BankPayment { to_address, .. } => {
+                    resp = resp.add_message(BankMsg::Send {
+                        to_address,
+                        amount: payment.clone(),
+                    });
+                }
+                MilestoneAction::Execute {
+                    contract_addr, msg, ..
+                } => {
+                    let msg = WasmMsg::Execute {
+                        contract_addr,
+                        msg,
+                        funds: payment.clone(),
+                    };
+                    resp = resp.add_submessage(SubMsg::reply_on_error(
+                        msg,
+                        MILESTONE_REPLY_ID_BASE + counter,
+                    ));
+                }
+            }
+        }
+        let event = MilestoneEvent {
+            counter,
+            payment,
+            unfunded: milestone.unfunded,
+        };
+        Ok(resp.add_event(event.into_event()))
     }
 }
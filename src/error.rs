@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use crate::msg::Role;
+use cosmwasm_std::{Coin, Decimal, StdError, Timestamp};
+use cw_utils::{Expiration, ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,8 +8,141 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
-    #[error("Unauthorized - only {owner} can call it")]
-    Unauthorized { owner: String },
+    #[error("Unauthorized - missing {role} role")]
+    MissingRole { role: Role },
+
+    #[error("cannot revoke the last admin")]
+    LastAdmin,
+
+    #[error("donations are paused")]
+    Paused,
+
+    #[error("{donor} is not allowed to donate")]
+    DonorNotAllowed { donor: String },
+
+    #[error("donor has to wait until {until} before donating again")]
+    DonationCooldown { until: Expiration },
+
+    #[error("at most {max} counted donations are allowed until {until}")]
+    EpochLimitReached { max: u64, until: Expiration },
+
+    #[error("donation would exceed the contribution cap of {cap}")]
+    ContributionCapExceeded { cap: Coin },
+
+    #[error("{addr} is not a member of the group")]
+    NotGroupMember { addr: String },
+
+    #[error("{donor} is not eligible to donate without a valid Merkle proof")]
+    NotEligible { donor: String },
+
+    #[error("invalid Merkle hash {hash}")]
+    InvalidHash { hash: String },
+
+    #[error("withdrawals require multisig approval")]
+    MultisigRequired,
+
+    #[error("multisig is not configured")]
+    MultisigNotConfigured,
+
+    #[error("invalid multisig threshold {threshold} for {approvers} approvers")]
+    InvalidThreshold { threshold: u32, approvers: u32 },
+
+    #[error("{addr} is not a withdraw approver")]
+    NotApprover { addr: String },
+
+    #[error("withdraw proposal {id} already approved by {addr}")]
+    AlreadyApproved { id: u64, addr: String },
+
+    #[error("withdraw proposal {id} has expired")]
+    ProposalExpired { id: u64 },
+
+    #[error("withdraw proposal {id} was already executed")]
+    ProposalExecuted { id: u64 },
+
+    #[error("withdraw proposal {id} has {approvals} of {threshold} required approvals")]
+    NotEnoughApprovals {
+        id: u64,
+        approvals: u32,
+        threshold: u32,
+    },
+
+    #[error("withdraw proposal {id} cannot be executed before {execute_after}")]
+    WithdrawTimelocked { id: u64, execute_after: Timestamp },
+
+    #[error("withdraw amount cannot be empty")]
+    EmptyWithdraw,
+
+    #[error("action must be queued through the timelock")]
+    TimelockRequired,
+
+    #[error("action is not timelocked and cannot be queued")]
+    NotTimelocked,
+
+    #[error("queued action {id} cannot be executed before {execute_after}")]
+    TimelockNotElapsed { id: u64, execute_after: Timestamp },
+
+    #[error("vesting must end after it starts")]
+    InvalidVesting,
+
+    #[error("cannot withdraw {requested}, only {claimable} is vested")]
+    NotVested { requested: Coin, claimable: Coin },
+
+    #[error("beneficiary shares sum up to {total} instead of one")]
+    InvalidShares { total: Decimal },
+
+    #[error("memo cannot be longer than {max} characters")]
+    MemoTooLong { max: usize },
+
+    #[error("donation {id} has no memo")]
+    NoMemo { id: u64 },
+
+    #[error("milestone {counter} has to be above the current counter {current}")]
+    MilestoneReached { counter: u64, current: u64 },
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("scheduled donation amount cannot be zero")]
+    EmptySchedule,
+
+    #[error("scheduled donations have to be funded with exactly {expected}")]
+    InvalidScheduleFunds { expected: Coin },
+
+    #[error("only the donor can cancel schedule {id}")]
+    NotScheduleDonor { id: u64 },
+
+    #[error("matching ratio has to be above zero and at most {max}")]
+    InvalidMatchRatio { max: Decimal },
+
+    #[error("matching pools have to be funded with at least {min}")]
+    PoolTooSmall { min: Coin },
+
+    #[error("matching pool has to expire in the future, not {expires}")]
+    InvalidPoolExpiration { expires: Expiration },
+
+    #[error("only the sponsor of matching pool {id} can reclaim it")]
+    NotSponsor { id: u64 },
+
+    #[error("matching pool {id} can only be reclaimed after {expires}")]
+    PoolNotExpired { id: u64, expires: Expiration },
+
+    #[error("reward token is not configured")]
+    NoRewardToken,
+
+    #[error("badge collection {collection} is not a soulbound badge collection")]
+    TransferableBadges { collection: String },
+
+    #[error("badge {token_id} cannot be transferred")]
+    BadgeNotTransferable { token_id: String },
+
+    #[error("{0}")]
+    Cw721(#[from] cw721_base::ContractError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("unknown reply id: {id}")]
+    UnknownReply { id: u64 },
 
     #[error("migrating invalid contract: {0}")]
     InvalidName(String),
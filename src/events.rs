@@ -0,0 +1,254 @@
+//! Typed `counting.*` events emitted by the contract. The chain reports them with a `wasm-`
+//! prefix, e.g. `wasm-counting.donate`; the parsers accept both forms.
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Event, StdError, StdResult, Uint128};
+
+pub trait CountingEvent: Sized {
+    const TYPE: &'static str;
+
+    fn into_event(self) -> Event;
+
+    fn try_from_event(event: &Event) -> StdResult<Self>;
+}
+
+/// Parses every event of type `E` out of `events`, skipping events of other types.
+pub fn parse_events<E: CountingEvent>(events: &[Event]) -> StdResult<Vec<E>> {
+    events
+        .iter()
+        .filter(|event| is_type::<E>(event))
+        .map(E::try_from_event)
+        .collect()
+}
+
+fn is_type<E: CountingEvent>(event: &Event) -> bool {
+    event.ty.strip_prefix("wasm-").unwrap_or(&event.ty) == E::TYPE
+}
+
+fn ensure_type<E: CountingEvent>(event: &Event) -> StdResult<()> {
+    if !is_type::<E>(event) {
+        return Err(StdError::parse_err(
+            E::TYPE,
+            format!("event type is {}", event.ty),
+        ));
+    }
+    Ok(())
+}
+
+fn attr<'a, E: CountingEvent>(event: &'a Event, key: &str) -> StdResult<&'a str> {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+        .ok_or_else(|| StdError::parse_err(E::TYPE, format!("missing attribute {key}")))
+}
+
+fn parse_attr<E: CountingEvent, T: std::str::FromStr>(event: &Event, key: &str) -> StdResult<T> {
+    attr::<E>(event, key)?
+        .parse()
+        .map_err(|_| StdError::parse_err(E::TYPE, format!("invalid attribute {key}")))
+}
+
+/// Formats coins as a comma separated list like `10atom,5eth`.
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(Coin::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_coins<E: CountingEvent>(event: &Event, key: &str) -> StdResult<Vec<Coin>> {
+    let value = attr::<E>(event, key)?;
+    if value.is_empty() {
+        return Ok(vec![]);
+    }
+    value
+        .split(',')
+        .map(|coin| {
+            let split = coin
+                .find(|c: char| !c.is_ascii_digit())
+                .filter(|&split| split > 0)
+                .ok_or_else(|| StdError::parse_err(E::TYPE, format!("invalid coin {coin}")))?;
+            let (amount, denom) = coin.split_at(split);
+            Ok(Coin {
+                denom: denom.to_owned(),
+                amount: amount.parse::<Uint128>()?,
+            })
+        })
+        .collect()
+}
+
+#[cw_serde]
+pub struct DonateEvent {
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub counted: bool,
+    pub counter: u64,
+    pub donation_id: u64,
+}
+
+impl CountingEvent for DonateEvent {
+    const TYPE: &'static str = "counting.donate";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("donor", self.donor)
+            .add_attribute("funds", coins_to_string(&self.funds))
+            .add_attribute("counted", self.counted.to_string())
+            .add_attribute("counter", self.counter.to_string())
+            .add_attribute("donation_id", self.donation_id.to_string())
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            donor: Addr::unchecked(attr::<Self>(event, "donor")?),
+            funds: parse_coins::<Self>(event, "funds")?,
+            counted: parse_attr::<Self, _>(event, "counted")?,
+            counter: parse_attr::<Self, _>(event, "counter")?,
+            donation_id: parse_attr::<Self, _>(event, "donation_id")?,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct ParentForwardEvent {
+    pub parent: Addr,
+    pub funds: Vec<Coin>,
+}
+
+impl CountingEvent for ParentForwardEvent {
+    const TYPE: &'static str = "counting.parent_forward";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("parent", self.parent)
+            .add_attribute("funds", coins_to_string(&self.funds))
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            parent: Addr::unchecked(attr::<Self>(event, "parent")?),
+            funds: parse_coins::<Self>(event, "funds")?,
+        })
+    }
+}
+
+/// Emitted when the counter reaches a configured milestone
+#[cw_serde]
+pub struct MilestoneEvent {
+    pub counter: u64,
+    /// Funds sent by the milestone action
+    pub payment: Vec<Coin>,
+    /// The payment was skipped for lack of available funds
+    pub unfunded: bool,
+}
+
+impl CountingEvent for MilestoneEvent {
+    const TYPE: &'static str = "counting.milestone";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("counter", self.counter.to_string())
+            .add_attribute("payment", coins_to_string(&self.payment))
+            .add_attribute("unfunded", self.unfunded.to_string())
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            counter: parse_attr::<Self, _>(event, "counter")?,
+            payment: parse_coins::<Self>(event, "payment")?,
+            unfunded: parse_attr::<Self, _>(event, "unfunded")?,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct ResetEvent {
+    pub sender: Addr,
+    /// Counter value before the reset
+    pub previous: u64,
+}
+
+impl CountingEvent for ResetEvent {
+    const TYPE: &'static str = "counting.reset";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("sender", self.sender)
+            .add_attribute("previous", self.previous.to_string())
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            sender: Addr::unchecked(attr::<Self>(event, "sender")?),
+            previous: parse_attr::<Self, _>(event, "previous")?,
+        })
+    }
+}
+
+/// Emitted once per receiver of a withdrawal, including multisig and split payouts
+#[cw_serde]
+pub struct WithdrawEvent {
+    pub sender: Addr,
+    pub receiver: Addr,
+    pub amount: Vec<Coin>,
+}
+
+impl CountingEvent for WithdrawEvent {
+    const TYPE: &'static str = "counting.withdraw";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("sender", self.sender)
+            .add_attribute("receiver", self.receiver)
+            .add_attribute("amount", coins_to_string(&self.amount))
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            sender: Addr::unchecked(attr::<Self>(event, "sender")?),
+            receiver: Addr::unchecked(attr::<Self>(event, "receiver")?),
+            amount: parse_coins::<Self>(event, "amount")?,
+        })
+    }
+}
+
+/// Emitted when a configuration setting changes; `setting` names it, e.g. `minimal_donation`
+#[cw_serde]
+pub struct ConfigEvent {
+    pub sender: Addr,
+    pub setting: String,
+}
+
+impl ConfigEvent {
+    pub fn new(sender: &Addr, setting: &str) -> Self {
+        Self {
+            sender: sender.clone(),
+            setting: setting.to_owned(),
+        }
+    }
+}
+
+impl CountingEvent for ConfigEvent {
+    const TYPE: &'static str = "counting.config";
+
+    fn into_event(self) -> Event {
+        Event::new(Self::TYPE)
+            .add_attribute("sender", self.sender)
+            .add_attribute("setting", self.setting)
+    }
+
+    fn try_from_event(event: &Event) -> StdResult<Self> {
+        ensure_type::<Self>(event)?;
+        Ok(Self {
+            sender: Addr::unchecked(attr::<Self>(event, "sender")?),
+            setting: attr::<Self>(event, "setting")?.to_owned(),
+        })
+    }
+}
@@ -1,59 +1,193 @@
-#[cfg(not(feature = "library"))]
+#[cfg(not(any(feature = "library", feature = "badges")))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Reply, Response, StdResult,
 };
 use error::ContractError;
 use msg::{ExecMsg, InstantiateMsg};
 mod contract;
 pub mod error;
+pub mod events;
 pub mod msg;
+pub mod soulbound;
 mod state;
 
 #[cfg(any(test, feature = "tests"))]
 pub mod multitest;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+#[cfg_attr(not(any(feature = "library", feature = "badges")), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
-    contract::instantiate(deps, info, msg)
+    contract::instantiate(deps, env, info, msg)
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "badges")), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: Empty) -> Result<Response, ContractError> {
+    contract::migrate(deps, env)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    contract::migrate(deps)
+#[cfg_attr(not(any(feature = "library", feature = "badges")), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
+    contract::reply(deps, env, reply)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+#[cfg_attr(not(any(feature = "library", feature = "badges")), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecMsg,
+) -> Result<Response, ContractError> {
+    contract::exec::ensure_not_timelocked(deps.storage, &msg)?;
+    dispatch(deps, env, info, msg)
+}
+
+fn dispatch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecMsg,
 ) -> Result<Response, ContractError> {
     use contract::exec::*;
     use ExecMsg::*;
 
     match msg {
-        Donate {} => donate(deps, env, info).map_err(ContractError::from),
-        Reset { counter: _ } => reset(deps, info),
+        Donate { memo, proof } => donate(deps, env, info, memo, proof),
+        DonateFor {
+            beneficiary,
+            memo,
+            proof,
+        } => donate_for(deps, env, info, beneficiary, memo, proof),
+        Reset { counter: _ } => reset(deps, env, info),
         Withdraw {} => withdraw(deps, env, info),
+        GrantRole { role, addr } => grant_role(deps, info, role, addr),
+        RevokeRole { role, addr } => revoke_role(deps, info, role, addr),
+        UpdateMinimalDonation { minimal_donation } => {
+            update_minimal_donation(deps, info, minimal_donation)
+        }
+        UpdateParent { parent } => update_parent(deps, info, parent),
+        Pause {} => set_paused(deps, info, true),
+        Unpause {} => set_paused(deps, info, false),
+        UpdateMultisig { multisig } => update_multisig(deps, info, multisig),
+        ProposeWithdraw { amount, receiver } => propose_withdraw(deps, env, info, amount, receiver),
+        ApproveWithdraw { id } => approve_withdraw(deps, env, info, id),
+        ExecuteWithdraw { id } => execute_withdraw(deps, env, info, id),
+        UpdateTimelock { delay } => update_timelock(deps, info, delay),
+        QueueAction { action } => queue_action(deps, env, info, *action),
+        CancelAction { id } => cancel_action(deps, info, id),
+        UpdateVesting { vesting } => update_vesting(deps, info, vesting),
+        UpdateSplit { split } => update_split(deps, info, split),
+        ModerateMemo {
+            donation_id,
+            hidden,
+        } => moderate_memo(deps, info, donation_id, hidden),
+        AddHook { addr } => add_hook(deps, info, addr),
+        RemoveHook { addr } => remove_hook(deps, info, addr),
+        AddMilestone { counter, action } => add_milestone(deps, info, counter, action),
+        RemoveMilestone { counter } => remove_milestone(deps, info, counter),
+        UpdateRewardRate { rate } => update_reward_rate(deps, info, rate),
+        UpdateBadgeCollection { collection } => update_badge_collection(deps, info, collection),
+        UpdateAccessMode { mode } => update_access_mode(deps, info, mode),
+        UpdateMerkleRoot { root } => update_merkle_root(deps, info, root),
+        UpdateGroup { group } => update_group(deps, info, group),
+        UpdateRateLimit { rate_limit } => update_rate_limit(deps, info, rate_limit),
+        FundMatchingPool {
+            ratio,
+            cap,
+            expires,
+        } => fund_matching_pool(deps, env, info, ratio, cap, expires),
+        ReclaimMatchingPool { id } => reclaim_matching_pool(deps, env, info, id),
+        ScheduleDonation {
+            amount,
+            interval,
+            repetitions,
+            tip,
+        } => schedule_donation(deps, env, info, amount, interval, repetitions, tip),
+        CancelSchedule { id } => cancel_schedule(deps, info, id),
+        ProcessScheduled { limit } => process_scheduled(deps, env, info, limit),
+        UpdateAllowlist { add, remove } => update_allowlist(deps, info, add, remove),
+        UpdateDenylist { add, remove } => update_denylist(deps, info, add, remove),
+        ExecuteAction { id } => {
+            let action = take_queued_action(deps.branch(), &env, id)?;
+            let resp = dispatch(deps, env, info, action)?;
+            Ok(resp.add_attribute("queued_action_id", id.to_string()))
+        }
     }
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(any(feature = "library", feature = "badges")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use msg::QueryMsg::*;
     match msg {
         Value {} => to_binary(&contract::query::value(deps)?),
-        ValueIncremented { value } => {
-            let resp = msg::ValueResponse { value: value + 1 };
-            to_binary(&resp)
+        ValueAt { height } => to_binary(&contract::query::value_at(deps, height)?),
+        SimulateDonate {
+            sender,
+            funds,
+            proof,
+        } => to_binary(&contract::query::simulate_donate(
+            deps, env, sender, funds, proof,
+        )?),
+        MigrationPlan {} => to_binary(&contract::query::migration_plan(deps.storage)),
+        Roles {} => to_binary(&contract::query::roles(deps)?),
+        Multisig {} => to_binary(&contract::query::multisig(deps)?),
+        Proposal { id } => to_binary(&contract::query::proposal(deps, env, id)?),
+        Proposals { start_after, limit } => {
+            to_binary(&contract::query::proposals(deps, env, start_after, limit)?)
+        }
+        Timelock {} => to_binary(&contract::query::timelock(deps)?),
+        QueuedActions { start_after, limit } => {
+            to_binary(&contract::query::queued_actions(deps, start_after, limit)?)
+        }
+        Vested {} => to_binary(&contract::query::vested(deps, env)?),
+        Split {} => to_binary(&contract::query::split(deps)?),
+        Donation { id } => to_binary(&contract::query::donation(deps, id)?),
+        Donations {
+            donor,
+            from_time,
+            to_time,
+            start_after,
+            limit,
+        } => to_binary(&contract::query::donations(
+            deps,
+            donor,
+            from_time,
+            to_time,
+            start_after,
+            limit,
+        )?),
+        Stats {} => to_binary(&contract::query::stats(deps)?),
+        Hooks {} => to_binary(&contract::query::hooks(deps)?),
+        Milestones { start_after, limit } => {
+            to_binary(&contract::query::milestones(deps, start_after, limit)?)
+        }
+        Leaderboard { limit, start_after } => {
+            to_binary(&contract::query::leaderboard(deps, limit, start_after)?)
+        }
+        RewardToken {} => to_binary(&contract::query::reward_token(deps)?),
+        BadgeCollection {} => to_binary(&contract::query::badge_collection(deps)?),
+        AccessMode {} => to_binary(&contract::query::access_mode(deps)?),
+        MerkleRoot {} => to_binary(&contract::query::merkle_root(deps)?),
+        Group {} => to_binary(&contract::query::group(deps)?),
+        RateLimit {} => to_binary(&contract::query::rate_limit(deps)?),
+        Schedules { start_after, limit } => {
+            to_binary(&contract::query::schedules(deps, start_after, limit)?)
+        }
+        MatchingPools { start_after, limit } => {
+            to_binary(&contract::query::matching_pools(deps, start_after, limit)?)
+        }
+        Donor { addr } => to_binary(&contract::query::donor(deps, env, addr)?),
+        Allowlist { start_after, limit } => {
+            to_binary(&contract::query::allowlist(deps, start_after, limit)?)
+        }
+        Denylist { start_after, limit } => {
+            to_binary(&contract::query::denylist(deps, start_after, limit)?)
         }
+        Badges { donor } => to_binary(&contract::query::badges(deps, donor)?),
     }
 }
 
@@ -1,5 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Decimal};
+use cosmwasm_std::{to_binary, Addr, Binary, Coin, Decimal, StdResult, Timestamp, Uint128};
+use cw_utils::{Duration, Expiration};
+use std::fmt;
 
 #[cw_serde]
 pub struct Parent {
@@ -8,12 +10,189 @@ pub struct Parent {
     pub part: Decimal,
 }
 
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Role {
+    Admin,
+    Withdrawer,
+    Resetter,
+    ConfigManager,
+    Pauser,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Admin,
+        Role::Withdrawer,
+        Role::Resetter,
+        Role::ConfigManager,
+        Role::Pauser,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Withdrawer => "withdrawer",
+            Role::Resetter => "resetter",
+            Role::ConfigManager => "config_manager",
+            Role::Pauser => "pauser",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Soulbound badge minted to a donor crossing its threshold
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Badge {
+    FirstDonation,
+    TenDonations,
+    /// Among the ten donors with the highest total contribution
+    TopContributor,
+}
+
+impl Badge {
+    pub const ALL: [Badge; 3] = [
+        Badge::FirstDonation,
+        Badge::TenDonations,
+        Badge::TopContributor,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Badge::FirstDonation => "first_donation",
+            Badge::TenDonations => "ten_donations",
+            Badge::TopContributor => "top_contributor",
+        }
+    }
+}
+
+impl fmt::Display for Badge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum AccessMode {
+    Open,
+    /// Only addresses on the allowlist may donate
+    Allowlist,
+    /// Everyone except addresses on the denylist may donate
+    Denylist,
+}
+
+/// cw4 group whose members may donate
+#[cw_serde]
+pub struct Group {
+    pub addr: String,
+    /// Lowest member weight accepted
+    pub min_weight: u64,
+    /// Members can also reset the counter and withdraw
+    #[serde(default)]
+    pub grant_roles: bool,
+}
+
+#[cw_serde]
+pub struct Epoch {
+    pub length: Duration,
+    /// Counted donations a donor may make within a single epoch
+    pub max_counted: u64,
+}
+
+/// Per-donor limits, all of them optional
+#[cw_serde]
+pub struct RateLimit {
+    /// Time a donor has to wait between two donations
+    pub cooldown: Option<Duration>,
+    pub epoch: Option<Epoch>,
+    /// Maximum cumulative contribution of a donor in the campaign denom
+    pub max_contribution: Option<Uint128>,
+}
+
+/// Part of a donation matched by a sponsor's matching pool
+#[cw_serde]
+pub struct PoolMatch {
+    pub pool_id: u64,
+    pub amount: Coin,
+}
+
+#[cw_serde]
+pub struct Multisig {
+    pub approvers: Vec<String>,
+    pub threshold: u32,
+    /// Seconds after which an unexecuted withdraw proposal expires
+    pub proposal_ttl: u64,
+}
+
+#[cw_serde]
+pub enum VestingSchedule {
+    /// Donated funds vest linearly between `start` and `end`
+    Linear { start: Timestamp, end: Timestamp },
+    /// All donated funds vest at once at `at`
+    Cliff { at: Timestamp },
+}
+
+#[cw_serde]
+pub struct Beneficiary {
+    pub addr: String,
+    pub share: Decimal,
+}
+
+#[cw_serde]
+pub struct Split {
+    /// Shares have to sum up to one
+    pub beneficiaries: Vec<Beneficiary>,
+    /// Receives the rounding dust left after paying out all the shares
+    pub residual_receiver: String,
+}
+
+#[cw_serde]
+pub enum MilestoneAction {
+    /// Only emits the `counting.milestone` event
+    Event {},
+    BankPayment {
+        to_address: String,
+        amount: Vec<Coin>,
+    },
+    Execute {
+        contract_addr: String,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+}
+
+#[cw_serde]
+pub enum RewardRate {
+    /// Fixed amount minted for every counted donation
+    PerDonation { amount: Uint128 },
+    /// Minted per unit of the campaign denom donated
+    PerUnit { rate: Decimal },
+}
+
+/// cw20-base supporter token instantiated along with the contract, which becomes its minter
+#[cw_serde]
+pub struct RewardToken {
+    pub code_id: u64,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub rate: RewardRate,
+}
+
 #[cw_serde]
 pub struct InstantiateMsg {
     #[serde(default)]
     pub counter: u64,
     pub minimal_donation: Coin,
     pub parent: Option<Parent>,
+    pub reward_token: Option<RewardToken>,
 }
 
 #[cw_serde]
@@ -21,21 +200,591 @@ pub struct InstantiateMsg {
 pub enum QueryMsg {
     #[returns(ValueResponse)]
     Value {},
-    #[returns(ValueResponse)]
-    ValueIncremented { value: u64 },
+    /// Counter value at the beginning of the block at `height`, `None` before the contract
+    /// existed or was migrated to snapshot storage
+    #[returns(Option<ValueResponse>)]
+    ValueAt { height: u64 },
+    /// Runs the acceptance logic of `Donate` without executing it
+    #[returns(SimulateDonateResponse)]
+    SimulateDonate {
+        sender: String,
+        funds: Vec<Coin>,
+        #[serde(default)]
+        proof: Option<Vec<String>>,
+    },
+    #[returns(MigrationPlanResponse)]
+    MigrationPlan {},
+    #[returns(RolesResponse)]
+    Roles {},
+    #[returns(Option<MultisigResponse>)]
+    Multisig {},
+    #[returns(ProposalResponse)]
+    Proposal { id: u64 },
+    /// Lists all withdraw proposals, whatever their status
+    #[returns(ProposalsResponse)]
+    Proposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(TimelockResponse)]
+    Timelock {},
+    #[returns(QueuedActionsResponse)]
+    QueuedActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(VestedResponse)]
+    Vested {},
+    #[returns(Option<SplitResponse>)]
+    Split {},
+    #[returns(DonationResponse)]
+    Donation { id: u64 },
+    /// Donation history, oldest first; `from_time` is inclusive and `to_time` exclusive
+    #[returns(DonationsResponse)]
+    Donations {
+        donor: Option<String>,
+        from_time: Option<Timestamp>,
+        to_time: Option<Timestamp>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(StatsResponse)]
+    Stats {},
+    #[returns(HooksResponse)]
+    Hooks {},
+    #[returns(MilestonesResponse)]
+    Milestones {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Donors ordered by their total contribution in the campaign denom, highest first
+    #[returns(LeaderboardResponse)]
+    Leaderboard {
+        limit: Option<u32>,
+        start_after: Option<String>,
+    },
+    /// `None` until the reward token is instantiated
+    #[returns(Option<RewardTokenResponse>)]
+    RewardToken {},
+    #[returns(Option<Addr>)]
+    BadgeCollection {},
+    #[returns(AccessModeResponse)]
+    AccessMode {},
+    #[returns(MerkleRootResponse)]
+    MerkleRoot {},
+    #[returns(Option<GroupResponse>)]
+    Group {},
+    #[returns(Option<RateLimit>)]
+    RateLimit {},
+    #[returns(SchedulesResponse)]
+    Schedules {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(MatchingPoolsResponse)]
+    MatchingPools {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Contribution of a single donor along with the rate limits applied to it
+    #[returns(DonorStatusResponse)]
+    Donor { addr: String },
+    #[returns(AccessListResponse)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(AccessListResponse)]
+    Denylist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(BadgesResponse)]
+    Badges { donor: String },
 }
 
 #[cw_serde]
 pub enum ExecMsg {
-    Donate {},
+    Donate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        memo: Option<String>,
+        /// Hex encoded Merkle proof of the sender's eligibility, only needed for the first
+        /// donation after a root is published
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proof: Option<Vec<String>>,
+    },
+    /// Donates the sent funds crediting `beneficiary` as the donor
+    DonateFor {
+        beneficiary: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        memo: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proof: Option<Vec<String>>,
+    },
     Reset {
         #[serde(default)]
         counter: u64,
     },
     Withdraw {},
+    GrantRole {
+        role: Role,
+        addr: String,
+    },
+    RevokeRole {
+        role: Role,
+        addr: String,
+    },
+    UpdateMinimalDonation {
+        minimal_donation: Coin,
+    },
+    UpdateParent {
+        parent: Option<Parent>,
+    },
+    Pause {},
+    Unpause {},
+    UpdateMultisig {
+        multisig: Option<Multisig>,
+    },
+    ProposeWithdraw {
+        amount: Vec<Coin>,
+        receiver: String,
+    },
+    ApproveWithdraw {
+        id: u64,
+    },
+    /// Sends the funds of an approved proposal, no earlier than the timelock delay after it
+    /// reached the approval threshold
+    ExecuteWithdraw {
+        id: u64,
+    },
+    /// Seconds a timelocked action has to wait in the queue; zero disables the timelock
+    UpdateTimelock {
+        delay: u64,
+    },
+    QueueAction {
+        action: Box<ExecMsg>,
+    },
+    CancelAction {
+        id: u64,
+    },
+    ExecuteAction {
+        id: u64,
+    },
+    UpdateVesting {
+        vesting: Option<VestingSchedule>,
+    },
+    UpdateSplit {
+        split: Option<Split>,
+    },
+    ModerateMemo {
+        donation_id: u64,
+        hidden: bool,
+    },
+    /// Registers a contract receiving `DonationHook` on every donation
+    AddHook {
+        addr: String,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    /// Fires `action` once the counter reaches `counter`
+    AddMilestone {
+        counter: u64,
+        action: MilestoneAction,
+    },
+    RemoveMilestone {
+        counter: u64,
+    },
+    UpdateRewardRate {
+        rate: RewardRate,
+    },
+    /// Collection badges are minted from, an instance of the `soulbound` contract with this
+    /// contract as its minter. `None` stops issuing badges.
+    UpdateBadgeCollection {
+        collection: Option<String>,
+    },
+    UpdateAccessMode {
+        mode: AccessMode,
+    },
+    /// Hex encoded sha256 Merkle root of the addresses eligible to donate; `None` lets
+    /// everyone donate again
+    UpdateMerkleRoot {
+        root: Option<String>,
+    },
+    UpdateGroup {
+        group: Option<Group>,
+    },
+    UpdateRateLimit {
+        rate_limit: Option<RateLimit>,
+    },
+    /// Opens a pool with the sent campaign denom funds, at least the minimal donation, matching
+    /// every counted donation with `ratio` of its campaign denom amount, at most `cap` per
+    /// donation, until `expires`. The ratio cannot be above ten
+    FundMatchingPool {
+        ratio: Decimal,
+        cap: Uint128,
+        expires: Expiration,
+    },
+    /// Sends the unused funds of an expired pool back to its sponsor
+    ReclaimMatchingPool {
+        id: u64,
+    },
+    /// Prefunds `repetitions` donations of `amount`, the first one due right away and each
+    /// next one `interval` after the previous was processed. The sent funds have to cover
+    /// `amount` plus `tip` for every repetition.
+    ScheduleDonation {
+        amount: Coin,
+        interval: Duration,
+        repetitions: u32,
+        /// Paid to whoever processes each of the donations
+        #[serde(default)]
+        tip: Uint128,
+    },
+    /// Refunds the donations of the schedule not processed yet
+    CancelSchedule {
+        id: u64,
+    },
+    /// Looks at up to `limit` schedules, resuming after the ones looked at by the previous call,
+    /// and executes the due ones; callable by anyone
+    ProcessScheduled {
+        limit: Option<u32>,
+    },
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateDenylist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+/// Sent to every registered hook contract on each donation
+#[cw_serde]
+pub struct DonationHookMsg {
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    /// Counter value after the donation
+    pub counter: u64,
+}
+
+impl DonationHookMsg {
+    /// Serializes the message wrapped in `HookExecuteMsg`, as hook contracts expect it.
+    pub fn into_binary(self) -> StdResult<Binary> {
+        to_binary(&HookExecuteMsg::DonationHook(self))
+    }
+}
+
+/// Execute message variant hook contracts have to implement
+#[cw_serde]
+pub enum HookExecuteMsg {
+    DonationHook(DonationHookMsg),
+}
+
+/// `Response::data` of `Donate`
+#[cw_serde]
+pub struct DonateResponse {
+    pub counter: u64,
+    pub counted: bool,
+    pub donation_id: u64,
+    /// Funds forwarded to the donating parent by this donation
+    pub forwarded: Vec<Coin>,
+}
+
+/// `Response::data` of `Withdraw` and `ExecuteWithdraw`
+#[cw_serde]
+pub struct WithdrawResponse {
+    pub sent: Vec<Coin>,
+}
+
+/// `Response::data` of `Reset`
+#[cw_serde]
+pub struct ResetResponse {
+    pub previous: u64,
 }
 
 #[cw_serde]
 pub struct ValueResponse {
     pub value: u64,
 }
+
+#[cw_serde]
+pub struct SimulateDonateResponse {
+    pub counted: bool,
+    /// Counter value after the donation
+    pub counter: u64,
+    pub parent_forward: bool,
+    /// Funds which would be forwarded to the donating parent
+    pub forwarded: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct StateResponse {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub owner: Addr,
+    pub donating_parent: Option<u64>,
+}
+
+#[cw_serde]
+pub struct MigrationPlanResponse {
+    pub contract: Option<String>,
+    pub from_version: Option<String>,
+    pub to_version: String,
+    pub steps: Vec<String>,
+    pub state: Option<StateResponse>,
+    pub incompatibility: Option<String>,
+}
+
+#[cw_serde]
+pub struct RoleGrant {
+    pub role: Role,
+    pub addr: Addr,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub roles: Vec<RoleGrant>,
+}
+
+#[cw_serde]
+pub struct AccessModeResponse {
+    pub mode: AccessMode,
+}
+
+#[cw_serde]
+pub struct AccessListResponse {
+    pub addresses: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct MerkleRootResponse {
+    pub root: Option<String>,
+}
+
+#[cw_serde]
+pub struct GroupResponse {
+    pub addr: Addr,
+    pub min_weight: u64,
+    pub grant_roles: bool,
+}
+
+#[cw_serde]
+pub struct MultisigResponse {
+    pub approvers: Vec<Addr>,
+    pub threshold: u32,
+    pub proposal_ttl: u64,
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum ProposalStatus {
+    /// Can still be approved and executed
+    Open,
+    Executed,
+    /// Expired before it was executed
+    Expired,
+}
+
+#[cw_serde]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposer: Addr,
+    pub amount: Vec<Coin>,
+    pub receiver: Addr,
+    pub expires: Timestamp,
+    pub executed: bool,
+    pub status: ProposalStatus,
+    pub approvals: Vec<Addr>,
+    /// Earliest time the proposal can be executed, once it has enough approvals
+    pub executable_after: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct ProposalsResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[cw_serde]
+pub struct TimelockResponse {
+    pub delay: u64,
+}
+
+#[cw_serde]
+pub struct QueuedActionResponse {
+    pub id: u64,
+    pub action: ExecMsg,
+    pub announcer: Addr,
+    pub execute_after: Timestamp,
+}
+
+#[cw_serde]
+pub struct QueuedActionsResponse {
+    pub actions: Vec<QueuedActionResponse>,
+}
+
+#[cw_serde]
+pub struct VestedResponse {
+    pub vesting: Option<VestingSchedule>,
+    pub claimable: Vec<Coin>,
+    pub locked: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct BeneficiaryResponse {
+    pub addr: Addr,
+    pub share: Decimal,
+}
+
+#[cw_serde]
+pub struct SplitResponse {
+    pub beneficiaries: Vec<BeneficiaryResponse>,
+    pub residual_receiver: Addr,
+}
+
+#[cw_serde]
+pub struct DonationResponse {
+    pub id: u64,
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub counted: bool,
+    pub height: u64,
+    pub time: Timestamp,
+    /// Always `None` for memos hidden by moderation
+    pub memo: Option<String>,
+    pub memo_hidden: bool,
+    /// Sender of the funds when donated on behalf of `donor`
+    pub funded_by: Option<Addr>,
+    pub matches: Vec<PoolMatch>,
+}
+
+#[cw_serde]
+pub struct DonationsResponse {
+    pub donations: Vec<DonationResponse>,
+}
+
+#[cw_serde]
+pub struct DonorResponse {
+    pub addr: Addr,
+    pub amount: Uint128,
+    pub donations: u64,
+}
+
+#[cw_serde]
+pub struct DonorStatusResponse {
+    pub addr: Addr,
+    pub amount: Uint128,
+    pub donations: u64,
+    /// `None` when the donor can donate right away
+    pub cooldown_ends: Option<Expiration>,
+    /// Counted donations left in the current epoch, `None` without an epoch limit
+    pub epoch_remaining: Option<u64>,
+    pub epoch_ends: Option<Expiration>,
+    /// Contribution left until the cap, `None` without a cap
+    pub contribution_remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct LeaderboardResponse {
+    pub denom: String,
+    pub donors: Vec<DonorResponse>,
+}
+
+#[cw_serde]
+pub struct LargestDonationResponse {
+    pub id: u64,
+    pub donor: Addr,
+    pub amount: Coin,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub counted_donations: u64,
+    pub uncounted_donations: u64,
+    pub unique_donors: u64,
+    pub received: Vec<Coin>,
+    pub withdrawn: Vec<Coin>,
+    /// Funds forwarded to the donating parent
+    pub forwarded: Vec<Coin>,
+    /// Largest single donation in the campaign denom
+    pub largest_donation: Option<LargestDonationResponse>,
+    pub parent_forwards: u64,
+    /// Funds added by matching pools
+    pub matched: Vec<Coin>,
+    /// Funds sent by milestone actions
+    pub milestone_payouts: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct MatchingPoolResponse {
+    pub id: u64,
+    pub sponsor: Addr,
+    pub ratio: Decimal,
+    pub cap: Uint128,
+    pub expires: Expiration,
+    /// Funds left for matching or to be reclaimed
+    pub remaining: Coin,
+    pub matched: Uint128,
+}
+
+#[cw_serde]
+pub struct ScheduleResponse {
+    pub id: u64,
+    pub donor: Addr,
+    pub amount: Coin,
+    pub tip: Uint128,
+    pub interval: Duration,
+    /// Donations left to process
+    pub remaining: u32,
+    pub next: Expiration,
+}
+
+#[cw_serde]
+pub struct SchedulesResponse {
+    pub schedules: Vec<ScheduleResponse>,
+}
+
+#[cw_serde]
+pub struct MatchingPoolsResponse {
+    pub pools: Vec<MatchingPoolResponse>,
+}
+
+#[cw_serde]
+pub struct MilestoneResponse {
+    pub counter: u64,
+    pub action: MilestoneAction,
+    pub fired_height: Option<u64>,
+    pub fired_time: Option<Timestamp>,
+    /// The milestone fired, but its payment was skipped for lack of available funds
+    pub unfunded: bool,
+    /// The milestone fired, but the message it executed failed and its funds stayed in the
+    /// contract
+    pub failed: bool,
+}
+
+#[cw_serde]
+pub struct MilestonesResponse {
+    pub milestones: Vec<MilestoneResponse>,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct RewardTokenResponse {
+    pub address: Addr,
+    pub rate: RewardRate,
+}
+
+#[cw_serde]
+pub struct BadgeResponse {
+    pub badge: Badge,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct BadgesResponse {
+    pub badges: Vec<BadgeResponse>,
+}
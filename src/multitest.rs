@@ -1,11 +1,19 @@
-use cosmwasm_std::{Addr, Coin, Empty, StdResult};
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, MemoryStorage, StdResult, Storage, Timestamp};
 use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_utils::{Duration, Expiration};
 
 use crate::{
     error::ContractError,
     execute, instantiate, migrate,
-    msg::{ExecMsg, InstantiateMsg, Parent, QueryMsg, ValueResponse},
-    query,
+    msg::{
+        AccessListResponse, AccessMode, BadgesResponse, DonationResponse, DonationsResponse,
+        DonorStatusResponse, ExecMsg, HooksResponse, InstantiateMsg, LeaderboardResponse,
+        MigrationPlanResponse, MilestoneAction, MilestonesResponse, Multisig, Parent,
+        ProposalsResponse, QueryMsg, QueuedActionsResponse, RateLimit, RewardRate, RewardToken,
+        RewardTokenResponse, Role, RolesResponse, SchedulesResponse, SimulateDonateResponse, Split,
+        StatsResponse, ValueResponse, VestedResponse, VestingSchedule,
+    },
+    query, reply,
 };
 
 #[cfg(test)]
@@ -21,11 +29,14 @@ impl CountingContract {
 
     #[track_caller]
     pub fn store_code(app: &mut App) -> u64 {
-        let contract = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_migrate(migrate);
         app.store_code(Box::new(contract))
     }
 
     #[track_caller]
+    #[allow(clippy::too_many_arguments)]
     pub fn instantiate<'a>(
         app: &mut App,
         code_id: u64,
@@ -44,7 +55,8 @@ impl CountingContract {
             &InstantiateMsg {
                 minimal_donation,
                 counter,
-                parent: parent,
+                parent,
+                reward_token: None,
             },
             &[],
             label,
@@ -54,6 +66,32 @@ impl CountingContract {
         .map(CountingContract)
     }
 
+    #[track_caller]
+    pub fn instantiate_with_reward_token(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        label: &str,
+        minimal_donation: Coin,
+        reward_token: RewardToken,
+    ) -> StdResult<CountingContract> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            &InstantiateMsg {
+                minimal_donation,
+                counter: 0,
+                parent: None,
+                reward_token: Some(reward_token),
+            },
+            &[],
+            label,
+            None,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(CountingContract)
+    }
+
     #[track_caller]
     pub fn donate(
         &self,
@@ -61,9 +99,121 @@ impl CountingContract {
         sender: &Addr,
         funds: &[Coin],
     ) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Donate {}, funds)
-            .map_err(|err| err.downcast::<ContractError>().unwrap())
-            .map(|_| ())
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn donate_with_memo(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        funds: &[Coin],
+        memo: &str,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Donate {
+                memo: Some(memo.to_owned()),
+                proof: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn donate_for(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        beneficiary: &Addr,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::DonateFor {
+                beneficiary: beneficiary.to_string(),
+                memo: None,
+                proof: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn donate_with_proof(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        funds: &[Coin],
+        proof: &[&str],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: Some(proof.iter().map(|hash| hash.to_string()).collect()),
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_merkle_root(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        root: Option<&str>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateMerkleRoot {
+                root: root.map(str::to_owned),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn moderate_memo(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        donation_id: u64,
+        hidden: bool,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ModerateMemo {
+                donation_id,
+                hidden,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
     }
 
     #[track_caller]
@@ -73,12 +223,694 @@ impl CountingContract {
             .map(|_| ())
     }
 
+    #[track_caller]
+    pub fn reset(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Reset { counter: 0 },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn grant_role(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        role: Role,
+        addr: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::GrantRole {
+                role,
+                addr: addr.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn revoke_role(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        role: Role,
+        addr: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RevokeRole {
+                role,
+                addr: addr.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn pause(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Pause {}, &[])
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_multisig(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        multisig: impl Into<Option<Multisig>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateMultisig {
+                multisig: multisig.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose_withdraw(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        amount: &[Coin],
+        receiver: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeWithdraw {
+                amount: amount.to_vec(),
+                receiver: receiver.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn approve_withdraw(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ApproveWithdraw { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn execute_withdraw(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ExecuteWithdraw { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_timelock(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        delay: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateTimelock { delay },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn queue_action(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        action: ExecMsg,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::QueueAction {
+                action: Box::new(action),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel_action(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CancelAction { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn execute_action(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ExecuteAction { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_vesting(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        vesting: impl Into<Option<VestingSchedule>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateVesting {
+                vesting: vesting.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_split(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        split: impl Into<Option<Split>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateSplit {
+                split: split.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn add_hook(&self, app: &mut App, sender: &Addr, hook: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::AddHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn remove_hook(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        hook: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RemoveHook {
+                addr: hook.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_reward_rate(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        rate: RewardRate,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateRewardRate { rate },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_badge_collection(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        collection: Option<&Addr>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateBadgeCollection {
+                collection: collection.map(Addr::to_string),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_access_mode(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        mode: AccessMode,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateAccessMode { mode },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_access_list(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        mode: AccessMode,
+        add: &[&Addr],
+        remove: &[&Addr],
+    ) -> Result<(), ContractError> {
+        let add = add.iter().map(|addr| addr.to_string()).collect();
+        let remove = remove.iter().map(|addr| addr.to_string()).collect();
+        let msg = match mode {
+            AccessMode::Denylist => ExecMsg::UpdateDenylist { add, remove },
+            _ => ExecMsg::UpdateAllowlist { add, remove },
+        };
+        app.execute_contract(sender.clone(), self.0.clone(), &msg, &[])
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_rate_limit(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateRateLimit { rate_limit },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn fund_matching_pool(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        ratio: Decimal,
+        cap: u128,
+        expires: Expiration,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::FundMatchingPool {
+                ratio,
+                cap: cap.into(),
+                expires,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn reclaim_matching_pool(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ReclaimMatchingPool { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    #[allow(clippy::too_many_arguments)]
+    pub fn schedule_donation(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        amount: Coin,
+        interval: Duration,
+        repetitions: u32,
+        tip: u128,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ScheduleDonation {
+                amount,
+                interval,
+                repetitions,
+                tip: tip.into(),
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel_schedule(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CancelSchedule { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn process_scheduled(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        limit: impl Into<Option<u32>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProcessScheduled {
+                limit: limit.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn add_milestone(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        counter: u64,
+        action: MilestoneAction,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::AddMilestone { counter, action },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn query_schedules(&self, app: &App) -> StdResult<SchedulesResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Schedules {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
     #[track_caller]
     pub fn query_value(&self, app: &App) -> StdResult<ValueResponse> {
         app.wrap()
             .query_wasm_smart(self.0.clone(), &QueryMsg::Value {})
     }
 
+    #[track_caller]
+    pub fn simulate_donate(
+        &self,
+        app: &App,
+        sender: &Addr,
+        funds: &[Coin],
+    ) -> StdResult<SimulateDonateResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::SimulateDonate {
+                sender: sender.to_string(),
+                funds: funds.to_vec(),
+                proof: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_value_at(&self, app: &App, height: u64) -> StdResult<Option<ValueResponse>> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::ValueAt { height })
+    }
+
+    #[track_caller]
+    pub fn query_roles(&self, app: &App) -> StdResult<RolesResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Roles {})
+    }
+
+    #[track_caller]
+    pub fn query_proposals(&self, app: &App) -> StdResult<ProposalsResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Proposals {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_queued_actions(&self, app: &App) -> StdResult<QueuedActionsResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::QueuedActions {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_vested(&self, app: &App) -> StdResult<VestedResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Vested {})
+    }
+
+    #[track_caller]
+    pub fn query_donation(&self, app: &App, id: u64) -> StdResult<DonationResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Donation { id })
+    }
+
+    #[track_caller]
+    pub fn query_donations(
+        &self,
+        app: &App,
+        donor: Option<&Addr>,
+        from_time: impl Into<Option<Timestamp>>,
+        to_time: impl Into<Option<Timestamp>>,
+        start_after: impl Into<Option<u64>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<DonationsResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Donations {
+                donor: donor.map(Addr::to_string),
+                from_time: from_time.into(),
+                to_time: to_time.into(),
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_stats(&self, app: &App) -> StdResult<StatsResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Stats {})
+    }
+
+    #[track_caller]
+    pub fn query_hooks(&self, app: &App) -> StdResult<HooksResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Hooks {})
+    }
+
+    #[track_caller]
+    pub fn query_reward_token(&self, app: &App) -> StdResult<Option<RewardTokenResponse>> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::RewardToken {})
+    }
+
+    #[track_caller]
+    pub fn query_badges(&self, app: &App, donor: &Addr) -> StdResult<BadgesResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Badges {
+                donor: donor.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_allowlist(
+        &self,
+        app: &App,
+        start_after: Option<&Addr>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<AccessListResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Allowlist {
+                start_after: start_after.map(Addr::to_string),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_donor(&self, app: &App, addr: &Addr) -> StdResult<DonorStatusResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Donor {
+                addr: addr.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_milestones(&self, app: &App) -> StdResult<MilestonesResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Milestones {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_leaderboard(
+        &self,
+        app: &App,
+        limit: impl Into<Option<u32>>,
+        start_after: impl Into<Option<String>>,
+    ) -> StdResult<LeaderboardResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Leaderboard {
+                limit: limit.into(),
+                start_after: start_after.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_migration_plan(&self, app: &App) -> StdResult<MigrationPlanResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::MigrationPlan {})
+    }
+
+    /// Checks a migration of any deployed contract, including ones running older code which
+    /// do not know the `MigrationPlan` query, by running the plan against its storage dump.
+    #[track_caller]
+    pub fn dry_run_migration(app: &App, contract: &Addr) -> MigrationPlanResponse {
+        let mut storage = MemoryStorage::new();
+        for (key, value) in app.dump_wasm_raw(contract) {
+            storage.set(&key, &value);
+        }
+        crate::contract::query::migration_plan(&storage)
+    }
+
     #[track_caller]
     pub fn migrate(app: &mut App, contract: Addr, code_id: u64, sender: &Addr) -> StdResult<Self> {
         app.migrate_contract(sender.clone(), contract.clone(), &Empty {}, code_id)
@@ -1,14 +1,33 @@
 use crate::{
+    contract::{self, MAX_MEMO_LENGTH},
     error::ContractError,
+    events::{
+        parse_events, ConfigEvent, DonateEvent, MilestoneEvent, ParentForwardEvent, ResetEvent,
+        WithdrawEvent,
+    },
     execute, instantiate,
-    msg::{Parent, ValueResponse},
+    msg::{
+        AccessMode, Badge, Beneficiary, DonateResponse, DonationHookMsg, DonationsResponse, Epoch,
+        ExecMsg, Group, HookExecuteMsg, MilestoneAction, Multisig, Parent, PoolMatch,
+        ProposalStatus, RateLimit, ResetResponse, RewardRate, RewardToken, Role, RoleGrant, Split,
+        StateResponse, ValueResponse, VestingSchedule, WithdrawResponse,
+    },
     multitest::CountingContract,
-    query,
+    query, soulbound,
     state::{State, STATE},
 };
-use cosmwasm_std::{coin, coins, Addr, Coin, Decimal, Empty};
+use cosmwasm_std::{
+    coin, coins, from_binary, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
+    MemoryStorage, MessageInfo, Response, StdError, StdResult, Uint128,
+};
 use counting_contract_0_1_4::multitest::CountingContract as CountingContract_0_1;
-use cw_multi_test::{App, Contract, ContractWrapper};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw4::{Cw4QueryMsg, Member, MemberResponse};
+use cw721::TokensResponse;
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration, PaymentError};
+use sha2::{Digest, Sha256};
 
 fn counting_contract() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(execute, instantiate, query);
@@ -17,6 +36,70 @@ fn counting_contract() -> Box<dyn Contract<Empty>> {
 
 const ATOM: &str = "atom";
 
+/// Hook subscriber remembering the last donation it was notified about
+fn hook_contract() -> Box<dyn Contract<Empty>> {
+    const LAST_HOOK: Item<DonationHookMsg> = Item::new("last_hook");
+
+    let contract = ContractWrapper::new(
+        |deps: DepsMut,
+         _env: Env,
+         _info: MessageInfo,
+         msg: HookExecuteMsg|
+         -> StdResult<Response> {
+            let HookExecuteMsg::DonationHook(hook) = msg;
+            LAST_HOOK.save(deps.storage, &hook)?;
+            Ok(Response::new())
+        },
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> {
+            to_binary(&LAST_HOOK.may_load(deps.storage)?)
+        },
+    );
+    Box::new(contract)
+}
+
+fn group_contract() -> Box<dyn Contract<Empty>> {
+    const MEMBERS: Map<&str, u64> = Map::new("members");
+
+    let contract = ContractWrapper::new(
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: DepsMut, _env: Env, _info: MessageInfo, msg: Vec<Member>| -> StdResult<Response> {
+            for member in msg {
+                MEMBERS.save(deps.storage, &member.addr, &member.weight)?;
+            }
+            Ok(Response::new())
+        },
+        |deps: Deps, _env: Env, msg: Cw4QueryMsg| -> StdResult<Binary> {
+            match msg {
+                Cw4QueryMsg::Member { addr, .. } => to_binary(&MemberResponse {
+                    weight: MEMBERS.may_load(deps.storage, &addr)?,
+                }),
+                _ => Err(StdError::generic_err("unsupported query")),
+            }
+        },
+    );
+    Box::new(contract)
+}
+
+fn broken_hook_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_deps: DepsMut,
+         _env: Env,
+         _info: MessageInfo,
+         _msg: HookExecuteMsg|
+         -> StdResult<Response> { Err(StdError::generic_err("broken hook")) },
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> { to_binary(&Empty {}) },
+    );
+    Box::new(contract)
+}
+
 #[test]
 fn query_value() {
     let mut app = App::default();
@@ -100,6 +183,134 @@ fn donate_with_funds() {
     );
 }
 
+#[test]
+fn value_at() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let start = app.block_info().height;
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 1);
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    app.update_block(|block| block.height += 1);
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    app.update_block(|block| block.height += 1);
+    contract.reset(&mut app, &owner).unwrap();
+    app.update_block(|block| block.height += 1);
+
+    let values: Vec<_> = (start..=start + 4)
+        .map(|height| {
+            contract
+                .query_value_at(&app, height)
+                .unwrap()
+                .map(|resp| resp.value)
+        })
+        .collect();
+    assert_eq!(values, vec![None, Some(0), Some(1), Some(2), Some(0)]);
+}
+
+#[test]
+fn simulate_donate() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Parent contract",
+        None,
+        None,
+        coin(0, ATOM),
+        None,
+    )
+    .unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+        }),
+    )
+    .unwrap();
+
+    let resp = contract
+        .simulate_donate(&app, &sender, &coins(5, ATOM))
+        .unwrap();
+    assert!(!resp.counted);
+    assert_eq!(resp.counter, 0);
+
+    let resp = contract
+        .simulate_donate(&app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert!(resp.counted);
+    assert_eq!(resp.counter, 1);
+    assert!(!resp.parent_forward);
+    assert_eq!(resp.forwarded, vec![]);
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    let resp = contract
+        .simulate_donate(&app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert!(resp.counted);
+    assert_eq!(resp.counter, 2);
+    assert!(resp.parent_forward);
+    assert_eq!(resp.forwarded, coins(2, ATOM));
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        resp.forwarded
+    );
+
+    contract.pause(&mut app, &owner).unwrap();
+    contract
+        .simulate_donate(&app, &sender, &coins(10, ATOM))
+        .unwrap_err();
+}
+
 #[test]
 fn withdraw() {
     let owner = Addr::unchecked("owner");
@@ -175,149 +386,2726 @@ fn unauthorized_withdraw() {
 
     assert_eq!(
         err,
-        ContractError::Unauthorized {
-            owner: owner.into()
+        ContractError::MissingRole {
+            role: Role::Withdrawer
         },
     );
 }
 
 #[test]
-fn migrate() {
-    let admin = Addr::unchecked("admin");
+fn roles() {
     let owner = Addr::unchecked("owner");
+    let member = Addr::unchecked("member");
     let sender = Addr::unchecked("sender");
 
     let mut app = App::new(|router, _api, storage| {
         router
             .bank
-            .init_balance(storage, &sender, coins(10, "atom"))
+            .init_balance(storage, &sender, coins(10, ATOM))
             .unwrap();
     });
-
-    let old_code_id = CountingContract_0_1::store_code(&mut app);
-    let new_code_id = CountingContract::store_code(&mut app);
-
-    let contract = CountingContract_0_1::instantiate(
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
         &mut app,
-        old_code_id,
+        code_id,
         &owner,
         "Counting contract",
-        Some(&admin),
         None,
-        coin(10, ATOM),
+        0,
+        Coin::new(10, ATOM),
+        None,
     )
     .unwrap();
 
+    // roles are listed in storage key order
+    let mut expected = Role::ALL.to_vec();
+    expected.sort_by_key(Role::as_str);
+    let resp = contract.query_roles(&app).unwrap();
+    assert_eq!(
+        resp.roles,
+        expected
+            .into_iter()
+            .map(|role| RoleGrant {
+                role,
+                addr: owner.clone()
+            })
+            .collect::<Vec<_>>()
+    );
+
+    let err = contract
+        .grant_role(&mut app, &member, Role::Withdrawer, &member)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole { role: Role::Admin });
+
     contract
         .donate(&mut app, &sender, &coins(10, ATOM))
         .unwrap();
+    contract
+        .grant_role(&mut app, &owner, Role::Withdrawer, &member)
+        .unwrap();
+    contract.withdraw(&mut app, &member).unwrap();
 
-    let contract =
-        CountingContract::migrate(&mut app, contract.into(), new_code_id, &admin).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(&member).unwrap(), vec![]);
 
-    let resp = contract.query_value(&app).unwrap();
-    assert_eq!(resp, ValueResponse { value: 1 });
+    let err = contract.reset(&mut app, &member).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::Resetter
+        }
+    );
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    contract
+        .revoke_role(&mut app, &owner, Role::Withdrawer, &member)
+        .unwrap();
+    let err = contract.withdraw(&mut app, &member).unwrap_err();
     assert_eq!(
-        state,
-        State {
-            counter: 1,
-            minimal_donation: coin(10, ATOM),
-            donating_parent: None,
-            owner
+        err,
+        ContractError::MissingRole {
+            role: Role::Withdrawer
         }
     );
+
+    let err = contract
+        .revoke_role(&mut app, &owner, Role::Admin, &owner)
+        .unwrap_err();
+    assert_eq!(err, ContractError::LastAdmin);
 }
 
 #[test]
-fn migrate_no_update() {
-    let admin = Addr::unchecked("admin");
+fn pause() {
     let owner = Addr::unchecked("owner");
-    let mut app = App::default();
+    let sender = Addr::unchecked("sender");
 
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, ATOM))
+            .unwrap();
+    });
     let code_id = CountingContract::store_code(&mut app);
-
     let contract = CountingContract::instantiate(
         &mut app,
         code_id,
         &owner,
         "Counting contract",
-        &admin,
         None,
-        coin(10, ATOM),
+        0,
+        Coin::new(10, ATOM),
         None,
     )
     .unwrap();
 
-    CountingContract::migrate(&mut app, contract.into(), code_id, &admin).unwrap();
+    let err = contract.pause(&mut app, &sender).unwrap_err();
+    assert_eq!(err, ContractError::MissingRole { role: Role::Pauser });
+
+    contract.pause(&mut app, &owner).unwrap();
+    let err = contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Paused);
 }
 
 #[test]
-fn donating_parent() {
+fn multisig_withdraw() {
     let owner = Addr::unchecked("owner");
     let sender = Addr::unchecked("sender");
+    let approver1 = Addr::unchecked("approver1");
+    let approver2 = Addr::unchecked("approver2");
+    let approver3 = Addr::unchecked("approver3");
+    let receiver = Addr::unchecked("receiver");
 
     let mut app = App::new(|router, _api, storage| {
         router
             .bank
-            .init_balance(storage, &sender, coins(20, "atom"))
+            .init_balance(storage, &sender, coins(10, ATOM))
             .unwrap();
     });
-
     let code_id = CountingContract::store_code(&mut app);
-
-    let parent_contract = CountingContract::instantiate(
+    let contract = CountingContract::instantiate(
         &mut app,
         code_id,
         &owner,
-        "Parent contract",
-        None,
+        "Counting contract",
         None,
-        coin(0, ATOM),
+        0,
+        Coin::new(10, ATOM),
         None,
     )
     .unwrap();
 
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let err = contract
+        .update_multisig(
+            &mut app,
+            &owner,
+            Multisig {
+                approvers: vec![approver1.to_string()],
+                threshold: 2,
+                proposal_ttl: 100,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidThreshold {
+            threshold: 2,
+            approvers: 1
+        }
+    );
+
+    contract
+        .update_multisig(
+            &mut app,
+            &owner,
+            Multisig {
+                approvers: vec![
+                    approver1.to_string(),
+                    approver2.to_string(),
+                    approver3.to_string(),
+                ],
+                threshold: 2,
+                proposal_ttl: 100,
+            },
+        )
+        .unwrap();
+
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::MultisigRequired);
+
+    let err = contract
+        .propose_withdraw(&mut app, &owner, &coins(6, ATOM), &receiver)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotApprover {
+            addr: owner.to_string()
+        }
+    );
+
+    contract
+        .propose_withdraw(&mut app, &approver1, &coins(6, ATOM), &receiver)
+        .unwrap();
+
+    let err = contract
+        .execute_withdraw(&mut app, &approver1, 0)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEnoughApprovals {
+            id: 0,
+            approvals: 1,
+            threshold: 2
+        }
+    );
+
+    contract.approve_withdraw(&mut app, &approver2, 0).unwrap();
+    let err = contract
+        .approve_withdraw(&mut app, &approver2, 0)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AlreadyApproved {
+            id: 0,
+            addr: approver2.to_string()
+        }
+    );
+
+    let resp = contract.query_proposals(&app).unwrap();
+    assert_eq!(resp.proposals.len(), 1);
+    assert_eq!(
+        resp.proposals[0].approvals,
+        [approver1.clone(), approver2.clone()]
+    );
+
+    contract.execute_withdraw(&mut app, &approver3, 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&receiver).unwrap(),
+        coins(6, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(4, ATOM)
+    );
+
+    let err = contract
+        .execute_withdraw(&mut app, &approver3, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ProposalExecuted { id: 0 });
+    let resp = contract.query_proposals(&app).unwrap();
+    assert_eq!(resp.proposals[0].status, ProposalStatus::Executed);
+
+    // Coins of the same denom are merged before checking what can be withdrawn
+    contract
+        .propose_withdraw(
+            &mut app,
+            &approver1,
+            &[coin(3, ATOM), coin(3, ATOM), coin(0, "eth")],
+            &receiver,
+        )
+        .unwrap();
+    contract.approve_withdraw(&mut app, &approver2, 1).unwrap();
+    let resp = contract.query_proposals(&app).unwrap();
+    assert_eq!(resp.proposals[1].amount, coins(6, ATOM));
+    assert_eq!(resp.proposals[1].status, ProposalStatus::Open);
+    let err = contract
+        .execute_withdraw(&mut app, &approver1, 1)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotVested {
+            requested: coin(6, ATOM),
+            claimable: coin(4, ATOM)
+        }
+    );
+}
+
+#[test]
+fn multisig_proposal_expiry() {
+    let owner = Addr::unchecked("owner");
+    let approver1 = Addr::unchecked("approver1");
+    let approver2 = Addr::unchecked("approver2");
+
+    let mut app = App::default();
+    let code_id = CountingContract::store_code(&mut app);
     let contract = CountingContract::instantiate(
         &mut app,
         code_id,
         &owner,
         "Counting contract",
         None,
+        0,
+        Coin::new(10, ATOM),
         None,
-        coin(10, ATOM),
-        Some(Parent {
-            addr: parent_contract.addr().to_string(),
-            donating_period: 2,
-            part: Decimal::percent(10),
-        }),
     )
     .unwrap();
 
     contract
-        .donate(&mut app, &sender, &coins(10, ATOM))
+        .update_multisig(
+            &mut app,
+            &owner,
+            Multisig {
+                approvers: vec![approver1.to_string(), approver2.to_string()],
+                threshold: 2,
+                proposal_ttl: 100,
+            },
+        )
         .unwrap();
     contract
-        .donate(&mut app, &sender, &coins(10, ATOM))
+        .propose_withdraw(&mut app, &approver1, &coins(6, ATOM), &owner)
         .unwrap();
 
-    let resp = parent_contract.query_value(&app).unwrap();
-    assert_eq!(resp, ValueResponse { value: 1 });
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
 
-    let resp = contract.query_value(&app).unwrap();
+    let err = contract
+        .approve_withdraw(&mut app, &approver2, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ProposalExpired { id: 0 });
+    let resp = contract.query_proposals(&app).unwrap();
+    assert_eq!(resp.proposals[0].status, ProposalStatus::Expired);
+}
+
+#[test]
+fn timelock() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract.update_timelock(&mut app, &owner, 100).unwrap();
+
+    let err = contract.reset(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired);
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired);
+
+    let err = contract
+        .queue_action(&mut app, &owner, ExecMsg::Pause {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotTimelocked);
+    let err = contract
+        .queue_action(&mut app, &sender, ExecMsg::Reset { counter: 0 })
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::Resetter
+        }
+    );
+
+    contract
+        .queue_action(&mut app, &owner, ExecMsg::Reset { counter: 0 })
+        .unwrap();
+    contract
+        .queue_action(&mut app, &owner, ExecMsg::Withdraw {})
+        .unwrap();
+
+    let resp = contract.query_queued_actions(&app).unwrap();
+    assert_eq!(
+        resp.actions
+            .iter()
+            .map(|queued| (queued.id, queued.action.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, ExecMsg::Reset { counter: 0 }),
+            (1, ExecMsg::Withdraw {})
+        ]
+    );
+
+    let execute_after = app.block_info().time.plus_seconds(100);
+    let err = contract.execute_action(&mut app, &owner, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TimelockNotElapsed {
+            id: 0,
+            execute_after
+        }
+    );
+
+    contract.cancel_action(&mut app, &owner, 1).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.execute_action(&mut app, &owner, 0).unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResponse { value: 0 });
+    assert_eq!(contract.query_queued_actions(&app).unwrap().actions, vec![]);
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    // Multisig withdrawals wait out the delay once approved
+    let approver = Addr::unchecked("approver");
+    contract
+        .queue_action(
+            &mut app,
+            &owner,
+            ExecMsg::UpdateMultisig {
+                multisig: Some(Multisig {
+                    approvers: vec![approver.to_string()],
+                    threshold: 1,
+                    proposal_ttl: 50,
+                }),
+            },
+        )
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.execute_action(&mut app, &owner, 2).unwrap();
+
+    contract
+        .propose_withdraw(&mut app, &approver, &coins(10, ATOM), &owner)
+        .unwrap();
+    let execute_after = app.block_info().time.plus_seconds(100);
+    let resp = contract.query_proposals(&app).unwrap();
+    assert_eq!(resp.proposals[0].executable_after, Some(execute_after));
+    let err = contract
+        .execute_withdraw(&mut app, &approver, 0)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawTimelocked {
+            id: 0,
+            execute_after
+        }
+    );
+    // The proposal stays open past its ttl until it can be executed
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.execute_withdraw(&mut app, &approver, 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn vesting() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let start = app.block_info().time;
+    let err = contract
+        .update_vesting(
+            &mut app,
+            &owner,
+            VestingSchedule::Linear { start, end: start },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidVesting);
+
+    contract
+        .update_vesting(
+            &mut app,
+            &owner,
+            VestingSchedule::Linear {
+                start,
+                end: start.plus_seconds(100),
+            },
+        )
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(100, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(40));
+    let resp = contract.query_vested(&app).unwrap();
+    assert_eq!(resp.claimable, coins(40, ATOM));
+    assert_eq!(resp.locked, coins(60, ATOM));
+
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(40, ATOM)
+    );
+    let resp = contract.query_vested(&app).unwrap();
+    assert_eq!(resp.claimable, vec![]);
+    assert_eq!(resp.locked, coins(60, ATOM));
+
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(100, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn split_withdraw() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let team1 = Addr::unchecked("team1");
+    let team2 = Addr::unchecked("team2");
+    let team3 = Addr::unchecked("team3");
+    let residual = Addr::unchecked("residual");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(15, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let beneficiary = |addr: &Addr, percent| Beneficiary {
+        addr: addr.to_string(),
+        share: Decimal::percent(percent),
+    };
+
+    let err = contract
+        .update_split(
+            &mut app,
+            &owner,
+            Split {
+                beneficiaries: vec![beneficiary(&team1, 50), beneficiary(&team2, 30)],
+                residual_receiver: residual.to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidShares {
+            total: Decimal::percent(80)
+        }
+    );
+
+    contract
+        .update_split(
+            &mut app,
+            &owner,
+            Split {
+                beneficiaries: vec![
+                    beneficiary(&team1, 50),
+                    beneficiary(&team2, 30),
+                    beneficiary(&team3, 20),
+                ],
+                residual_receiver: residual.to_string(),
+            },
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(15, ATOM))
+        .unwrap();
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(team1).unwrap(),
+        coins(7, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(team2).unwrap(),
+        coins(4, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(team3).unwrap(),
+        coins(3, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(residual).unwrap(),
+        coins(1, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(owner).unwrap(), vec![]);
+}
+
+#[test]
+fn events() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, vec![coin(20, ATOM), coin(10, "eth")])
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Parent contract",
+        None,
+        None,
+        coin(0, ATOM),
+        None,
+    )
+    .unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+        }),
+    )
+    .unwrap();
+
+    let resp = app
+        .execute_contract(
+            sender.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: None,
+            },
+            &[coin(20, ATOM), coin(10, "eth")],
+        )
+        .unwrap();
+    let donations: Vec<DonateEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(
+        donations[0],
+        DonateEvent {
+            donor: sender.clone(),
+            funds: vec![coin(20, ATOM), coin(10, "eth")],
+            counted: true,
+            counter: 1,
+            donation_id: 0,
+        }
+    );
+    let forwards: Vec<ParentForwardEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(
+        forwards,
+        vec![ParentForwardEvent {
+            parent: parent_contract.addr().clone(),
+            funds: vec![coin(2, ATOM), coin(1, "eth")],
+        }]
+    );
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Withdraw {},
+            &[],
+        )
+        .unwrap();
+    let withdrawals: Vec<WithdrawEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(
+        withdrawals,
+        vec![WithdrawEvent {
+            sender: owner.clone(),
+            receiver: owner.clone(),
+            amount: vec![coin(18, ATOM), coin(9, "eth")],
+        }]
+    );
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Reset { counter: 0 },
+            &[],
+        )
+        .unwrap();
+    let resets: Vec<ResetEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(
+        resets,
+        vec![ResetEvent {
+            sender: owner.clone(),
+            previous: 1,
+        }]
+    );
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::UpdateMinimalDonation {
+                minimal_donation: coin(5, ATOM),
+            },
+            &[],
+        )
+        .unwrap();
+    let changes: Vec<ConfigEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(changes, vec![ConfigEvent::new(&owner, "minimal_donation")]);
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::GrantRole {
+                role: Role::Pauser,
+                addr: sender.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    let changes: Vec<ConfigEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(changes, vec![ConfigEvent::new(&owner, "roles")]);
+}
+
+#[test]
+fn response_data() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let resp = app
+        .execute_contract(
+            sender.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: None,
+            },
+            &coins(5, ATOM),
+        )
+        .unwrap();
+    let data: DonateResponse = from_binary(&resp.data.unwrap()).unwrap();
+    assert_eq!(
+        data,
+        DonateResponse {
+            counter: 0,
+            counted: false,
+            donation_id: 0,
+            forwarded: vec![],
+        }
+    );
+
+    let resp = app
+        .execute_contract(
+            sender.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: None,
+            },
+            &coins(15, ATOM),
+        )
+        .unwrap();
+    let data: DonateResponse = from_binary(&resp.data.unwrap()).unwrap();
+    assert!(data.counted);
+    assert_eq!(data.counter, 1);
+    assert_eq!(data.donation_id, 1);
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Withdraw {},
+            &[],
+        )
+        .unwrap();
+    let data: WithdrawResponse = from_binary(&resp.data.unwrap()).unwrap();
+    assert_eq!(data.sent, coins(20, ATOM));
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Reset { counter: 0 },
+            &[],
+        )
+        .unwrap();
+    let data: ResetResponse = from_binary(&resp.data.unwrap()).unwrap();
+    assert_eq!(data, ResetResponse { previous: 1 });
+}
+
+#[test]
+fn donation_hooks() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let hook_id = app.store_code(hook_contract());
+    let broken_hook_id = app.store_code(broken_hook_contract());
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+    let hook = app
+        .instantiate_contract(hook_id, owner.clone(), &Empty {}, &[], "Hook", None)
+        .unwrap();
+    let broken_hook = app
+        .instantiate_contract(
+            broken_hook_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "Broken",
+            None,
+        )
+        .unwrap();
+
+    let err = contract.add_hook(&mut app, &sender, &hook).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::ConfigManager
+        }
+    );
+    contract.add_hook(&mut app, &owner, &hook).unwrap();
+    contract.add_hook(&mut app, &owner, &broken_hook).unwrap();
+    let resp = contract.query_hooks(&app).unwrap();
+    assert_eq!(resp.hooks.len(), 2);
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    let last: Option<DonationHookMsg> = app.wrap().query_wasm_smart(&hook, &Empty {}).unwrap();
+    assert_eq!(
+        last,
+        Some(DonationHookMsg {
+            donor: sender.clone(),
+            funds: coins(10, ATOM),
+            counter: 1,
+        })
+    );
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+
+    contract.remove_hook(&mut app, &owner, &hook).unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    let last: Option<DonationHookMsg> = app.wrap().query_wasm_smart(&hook, &Empty {}).unwrap();
+    assert_eq!(last.unwrap().counter, 1);
+    assert_eq!(contract.query_hooks(&app).unwrap().hooks, vec![broken_hook]);
+}
+
+#[test]
+fn milestones() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let charity = Addr::unchecked("charity");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(60, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .add_milestone(&mut app, &owner, 0, MilestoneAction::Event {})
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MilestoneReached {
+            counter: 0,
+            current: 0
+        }
+    );
+    let err = contract
+        .add_milestone(&mut app, &sender, 2, MilestoneAction::Event {})
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::ConfigManager
+        }
+    );
+
+    contract
+        .add_milestone(
+            &mut app,
+            &owner,
+            2,
+            MilestoneAction::BankPayment {
+                to_address: charity.to_string(),
+                amount: coins(5, ATOM),
+            },
+        )
+        .unwrap();
+    contract
+        .add_milestone(
+            &mut app,
+            &owner,
+            3,
+            MilestoneAction::BankPayment {
+                to_address: charity.to_string(),
+                amount: coins(100, ATOM),
+            },
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(&charity).unwrap(), vec![]);
+    app.update_block(|block| block.height += 1);
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&charity).unwrap(),
+        coins(5, ATOM)
+    );
+
+    let height = app.block_info().height;
+    let resp = contract.query_milestones(&app).unwrap();
+    let fired: Vec<_> = resp
+        .milestones
+        .iter()
+        .map(|milestone| (milestone.counter, milestone.fired_height))
+        .collect();
+    assert_eq!(fired, vec![(2, Some(height)), (3, None)]);
+
+    contract.reset(&mut app, &owner).unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&charity).unwrap(),
+        coins(5, ATOM)
+    );
+    let resp = contract.query_milestones(&app).unwrap();
+    assert_eq!(resp.milestones[0].fired_height, Some(height));
+
+    // A milestone which cannot be paid for fires without blocking the donation
+    let resp = app
+        .execute_contract(
+            sender.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                proof: None,
+            },
+            &coins(10, ATOM),
+        )
+        .unwrap();
+    let fired: Vec<MilestoneEvent> = parse_events(&resp.events).unwrap();
+    assert_eq!(
+        fired,
+        vec![MilestoneEvent {
+            counter: 3,
+            payment: vec![],
+            unfunded: true,
+        }]
+    );
+    assert_eq!(contract.query_value(&app).unwrap().value, 3);
+    let resp = contract.query_milestones(&app).unwrap();
+    assert!(resp.milestones[1].fired_height.is_some());
+    assert!(resp.milestones[1].unfunded);
+    assert_eq!(
+        app.wrap().query_all_balances(&charity).unwrap(),
+        coins(5, ATOM)
+    );
+    assert_eq!(
+        contract.query_stats(&app).unwrap().milestone_payouts,
+        coins(5, ATOM)
+    );
+
+    // A failing milestone message neither blocks the donation nor takes the funds along
+    contract
+        .add_milestone(
+            &mut app,
+            &owner,
+            4,
+            MilestoneAction::Execute {
+                contract_addr: contract.addr().to_string(),
+                msg: to_binary(&ExecMsg::Withdraw {}).unwrap(),
+                funds: coins(5, ATOM),
+            },
+        )
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 4);
+    let resp = contract.query_milestones(&app).unwrap();
+    assert!(resp.milestones[2].failed);
+    assert!(!resp.milestones[2].unfunded);
+    assert_eq!(
+        contract.query_stats(&app).unwrap().milestone_payouts,
+        coins(5, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(55, ATOM)
+    );
+}
+
+#[test]
+fn donation_memo() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .donate_with_memo(
+            &mut app,
+            &sender,
+            &coins(10, ATOM),
+            &"a".repeat(MAX_MEMO_LENGTH + 1),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MemoTooLong {
+            max: MAX_MEMO_LENGTH
+        }
+    );
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate_with_memo(&mut app, &sender, &coins(10, ATOM), "go team")
+        .unwrap();
+
+    let resp = contract.query_donation(&app, 1).unwrap();
+    assert_eq!(resp.donor, sender);
+    assert_eq!(resp.funds, coins(10, ATOM));
+    assert!(resp.counted);
+    assert_eq!(resp.memo.as_deref(), Some("go team"));
+
+    let err = contract
+        .moderate_memo(&mut app, &sender, 1, true)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingRole { role: Role::Admin });
+    let err = contract
+        .moderate_memo(&mut app, &owner, 0, true)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoMemo { id: 0 });
+
+    contract.moderate_memo(&mut app, &owner, 1, true).unwrap();
+    let resp = contract.query_donation(&app, 1).unwrap();
+    assert_eq!(resp.memo, None);
+    assert!(resp.memo_hidden);
+    assert_eq!(resp.funds, coins(10, ATOM));
+
+    contract.moderate_memo(&mut app, &owner, 1, false).unwrap();
+    let resp = contract.query_donation(&app, 1).unwrap();
+    assert_eq!(resp.memo.as_deref(), Some("go team"));
+}
+
+#[test]
+fn donation_history() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(100, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let start = app.block_info().time;
+    for (donor, amount) in [(&alice, 10), (&bob, 5), (&alice, 20), (&bob, 15)] {
+        contract
+            .donate(&mut app, donor, &coins(amount, ATOM))
+            .unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+    }
+
+    let ids = |resp: DonationsResponse| -> Vec<u64> {
+        resp.donations
+            .into_iter()
+            .map(|donation| donation.id)
+            .collect()
+    };
+
+    let resp = contract
+        .query_donations(&app, None, None, None, None, None)
+        .unwrap();
+    assert_eq!(ids(resp.clone()), vec![0, 1, 2, 3]);
+    assert_eq!(resp.donations[1].donor, bob);
+    assert!(!resp.donations[1].counted);
+    assert_eq!(resp.donations[2].time, start.plus_seconds(20));
+
+    let resp = contract
+        .query_donations(&app, Some(&alice), None, None, None, None)
+        .unwrap();
+    assert_eq!(ids(resp), vec![0, 2]);
+
+    let resp = contract
+        .query_donations(
+            &app,
+            None,
+            start.plus_seconds(10),
+            start.plus_seconds(30),
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(ids(resp), vec![1, 2]);
+
+    let resp = contract
+        .query_donations(&app, Some(&bob), start.plus_seconds(10), None, None, None)
+        .unwrap();
+    assert_eq!(ids(resp), vec![1, 3]);
+
+    let resp = contract
+        .query_donations(&app, None, None, None, None, 2)
+        .unwrap();
+    assert_eq!(ids(resp), vec![0, 1]);
+    let resp = contract
+        .query_donations(&app, None, None, None, 1, 2)
+        .unwrap();
+    assert_eq!(ids(resp), vec![2, 3]);
+}
+
+#[test]
+fn stats() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(100, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Parent contract",
+        None,
+        None,
+        coin(0, ATOM),
+        None,
+    )
+    .unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+        }),
+    )
+    .unwrap();
+
+    contract.donate(&mut app, &alice, &coins(10, ATOM)).unwrap();
+    contract.donate(&mut app, &alice, &coins(5, ATOM)).unwrap();
+    contract.donate(&mut app, &bob, &coins(20, ATOM)).unwrap();
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    let resp = contract.query_stats(&app).unwrap();
+    assert_eq!(resp.counted_donations, 2);
+    assert_eq!(resp.uncounted_donations, 1);
+    assert_eq!(resp.unique_donors, 2);
+    assert_eq!(resp.received, coins(35, ATOM));
+    assert_eq!(resp.forwarded, coins(3, ATOM));
+    assert_eq!(resp.withdrawn, coins(32, ATOM));
+    assert_eq!(resp.parent_forwards, 1);
+    let largest = resp.largest_donation.unwrap();
+    assert_eq!(largest.id, 2);
+    assert_eq!(largest.donor, bob);
+    assert_eq!(largest.amount, coin(20, ATOM));
+}
+
+#[test]
+fn leaderboard() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &bob, &carol] {
+            router
+                .bank
+                .init_balance(storage, donor, vec![coin(100, ATOM), coin(100, "eth")])
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        0,
+        Coin::new(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    contract.donate(&mut app, &alice, &coins(10, ATOM)).unwrap();
+    contract.donate(&mut app, &bob, &coins(30, ATOM)).unwrap();
+    contract.donate(&mut app, &alice, &coins(25, ATOM)).unwrap();
+    contract.donate(&mut app, &carol, &coins(5, ATOM)).unwrap();
+    contract
+        .donate(&mut app, &carol, &coins(90, "eth"))
+        .unwrap();
+
+    let resp = contract.query_leaderboard(&app, None, None).unwrap();
+    assert_eq!(resp.denom, ATOM);
+    let ranking: Vec<_> = resp
+        .donors
+        .iter()
+        .map(|donor| (donor.addr.clone(), donor.amount.u128(), donor.donations))
+        .collect();
+    assert_eq!(
+        ranking,
+        vec![(alice.clone(), 35, 2), (bob.clone(), 30, 1), (carol, 5, 1)]
+    );
+
+    let resp = contract.query_leaderboard(&app, 1, None).unwrap();
+    assert_eq!(resp.donors.len(), 1);
+    assert_eq!(resp.donors[0].addr, alice);
+
+    let resp = contract
+        .query_leaderboard(&app, 1, alice.to_string())
+        .unwrap();
+    assert_eq!(resp.donors.len(), 1);
+    assert_eq!(resp.donors[0].addr, bob);
+}
+
+#[test]
+fn migrate() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, "atom"))
+            .unwrap();
+    });
+
+    let old_code_id = CountingContract_0_1::store_code(&mut app);
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_1::instantiate(
+        &mut app,
+        old_code_id,
+        &owner,
+        "Counting contract",
+        Some(&admin),
+        None,
+        coin(10, ATOM),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let contract =
+        CountingContract::migrate(&mut app, contract.into(), new_code_id, &admin).unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResponse { value: 1 });
+    let height = app.block_info().height;
+    assert_eq!(contract.query_value_at(&app, height).unwrap(), None);
+    assert_eq!(
+        contract.query_value_at(&app, height + 1).unwrap(),
+        Some(ValueResponse { value: 1 })
+    );
+
+    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    assert_eq!(
+        state,
+        State {
+            minimal_donation: coin(10, ATOM),
+            donating_parent: None,
+            owner: owner.clone()
+        }
+    );
+
+    let resp = contract.query_roles(&app).unwrap();
+    assert_eq!(resp.roles.len(), Role::ALL.len());
+    assert!(resp.roles.iter().all(|grant| grant.addr == owner));
+}
+
+#[test]
+fn migration_dry_run() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, "atom"))
+            .unwrap();
+    });
+
+    let old_code_id = CountingContract_0_1::store_code(&mut app);
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_1::instantiate(
+        &mut app,
+        old_code_id,
+        &owner,
+        "Counting contract",
+        Some(&admin),
+        None,
+        coin(10, ATOM),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let contract: Addr = contract.into();
+    let plan = CountingContract::dry_run_migration(&app, &contract);
+    assert_eq!(plan.contract.as_deref(), Some("counting_contract"));
+    assert_eq!(plan.from_version.as_deref(), Some("0.1.4"));
+    assert_eq!(plan.incompatibility, None);
+    assert!(!plan.steps.is_empty());
+    assert_eq!(
+        plan.state,
+        Some(StateResponse {
+            counter: 1,
+            minimal_donation: coin(10, ATOM),
+            owner,
+            donating_parent: None,
+        })
+    );
+
+    // nothing was written by the dry run
+    STATE.query(&app.wrap(), contract.clone()).unwrap_err();
+
+    let contract = CountingContract::migrate(&mut app, contract, new_code_id, &admin).unwrap();
+    let plan = contract.query_migration_plan(&app).unwrap();
+    assert_eq!(plan.incompatibility, None);
+    assert_eq!(plan.steps, Vec::<String>::new());
+    assert_eq!(plan.from_version, Some(plan.to_version.clone()));
+}
+
+#[test]
+fn migration_dry_run_unsupported() {
+    let mut storage = MemoryStorage::new();
+    cw2::set_contract_version(&mut storage, "counting_contract", "0.0.1").unwrap();
+
+    let plan = contract::query::migration_plan(&storage);
+    assert_eq!(
+        plan.incompatibility,
+        Some(ContractError::UnsupportedVersion("0.0.1".to_owned()).to_string())
+    );
+    assert_eq!(plan.steps, Vec::<String>::new());
+    assert_eq!(plan.state, None);
+}
+
+#[test]
+fn migrate_no_update() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let mut app = App::default();
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        &admin,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    CountingContract::migrate(&mut app, contract.into(), code_id, &admin).unwrap();
+}
+
+#[test]
+fn donating_parent() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, "atom"))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Parent contract",
+        None,
+        None,
+        coin(0, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+        }),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResponse { value: 1 });
+
+    let resp = contract.query_value(&app).unwrap();
     assert_eq!(resp, ValueResponse { value: 2 });
 
-    assert_eq!(app.wrap().query_all_balances(owner).unwrap(), vec![]);
-    assert_eq!(app.wrap().query_all_balances(sender).unwrap(), vec![]);
+    assert_eq!(app.wrap().query_all_balances(owner).unwrap(), vec![]);
+    assert_eq!(app.wrap().query_all_balances(sender).unwrap(), vec![]);
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(18, ATOM)
+    );
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(2, ATOM)
+    );
+}
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn reward_token() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(30, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let cw20_id = app.store_code(cw20_contract());
+    let contract = CountingContract::instantiate_with_reward_token(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        coin(10, ATOM),
+        RewardToken {
+            code_id: cw20_id,
+            name: "Supporter".to_owned(),
+            symbol: "SUP".to_owned(),
+            decimals: 6,
+            rate: RewardRate::PerDonation {
+                amount: Uint128::new(100),
+            },
+        },
+    )
+    .unwrap();
+
+    let token = contract.query_reward_token(&app).unwrap().unwrap();
+    assert_eq!(
+        token.rate,
+        RewardRate::PerDonation {
+            amount: Uint128::new(100)
+        }
+    );
+    let balance = |app: &App| -> Uint128 {
+        let resp: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &token.address,
+                &Cw20QueryMsg::Balance {
+                    address: sender.to_string(),
+                },
+            )
+            .unwrap();
+        resp.balance
+    };
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(balance(&app), Uint128::new(100));
+
+    // Uncounted donations earn nothing per donation
+    contract.donate(&mut app, &sender, &coins(5, ATOM)).unwrap();
+    assert_eq!(balance(&app), Uint128::new(100));
+
+    let err = contract
+        .update_reward_rate(
+            &mut app,
+            &sender,
+            RewardRate::PerUnit {
+                rate: Decimal::one(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::ConfigManager
+        }
+    );
+    contract
+        .update_reward_rate(
+            &mut app,
+            &owner,
+            RewardRate::PerUnit {
+                rate: Decimal::percent(50),
+            },
+        )
+        .unwrap();
+
+    contract.donate(&mut app, &sender, &coins(5, ATOM)).unwrap();
+    assert_eq!(balance(&app), Uint128::new(102));
+}
+
+fn cw721_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    );
+    Box::new(contract)
+}
+
+fn badge_collection_contract() -> Box<dyn Contract<Empty>> {
+    let contract =
+        ContractWrapper::new(soulbound::execute, soulbound::instantiate, soulbound::query);
+    Box::new(contract)
+}
+
+#[test]
+fn badges() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let late = Addr::unchecked("late");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(200, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &late, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let cw721_id = app.store_code(cw721_contract());
+    let badges_id = app.store_code(badge_collection_contract());
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+    let init_msg = cw721_base::InstantiateMsg {
+        name: "Supporter badges".to_owned(),
+        symbol: "BADGE".to_owned(),
+        minter: contract.addr().to_string(),
+    };
+    let transferable = app
+        .instantiate_contract(cw721_id, owner.clone(), &init_msg, &[], "Badges", None)
+        .unwrap();
+    let collection = app
+        .instantiate_contract(badges_id, owner.clone(), &init_msg, &[], "Badges", None)
+        .unwrap();
+
+    let err = contract
+        .update_badge_collection(&mut app, &owner, Some(&transferable))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TransferableBadges {
+            collection: transferable.to_string()
+        }
+    );
+
+    let err = contract
+        .update_badge_collection(&mut app, &sender, Some(&collection))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::ConfigManager
+        }
+    );
+    contract
+        .update_badge_collection(&mut app, &owner, Some(&collection))
+        .unwrap();
+
+    // Uncounted donations don't earn the first donation badge
+    contract.donate(&mut app, &sender, &coins(1, ATOM)).unwrap();
+    let resp = contract.query_badges(&app, &sender).unwrap();
+    let badges: Vec<_> = resp.badges.iter().map(|badge| badge.badge).collect();
+    assert_eq!(badges, [Badge::TopContributor]);
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    let resp = contract.query_badges(&app, &sender).unwrap();
+    let badges: Vec<_> = resp.badges.iter().map(|badge| badge.badge).collect();
+    assert_eq!(badges, [Badge::FirstDonation, Badge::TopContributor]);
+
+    let tokens: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &collection,
+            &cw721_base::QueryMsg::<Empty>::Tokens {
+                owner: sender.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tokens.tokens.len(), 2);
+    assert!(tokens.tokens.contains(&resp.badges[0].token_id));
+
+    let err = app
+        .execute_contract(
+            sender.clone(),
+            collection.clone(),
+            &soulbound::ExecuteMsg::TransferNft {
+                recipient: owner.to_string(),
+                token_id: resp.badges[0].token_id.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BadgeNotTransferable {
+            token_id: resp.badges[0].token_id.clone()
+        }
+    );
+
+    // Donations below the minimum don't count towards the badge
+    for _ in 0..8 {
+        contract.donate(&mut app, &sender, &coins(1, ATOM)).unwrap();
+    }
+    assert_eq!(
+        contract.query_badges(&app, &sender).unwrap().badges.len(),
+        2
+    );
+    for _ in 0..9 {
+        contract
+            .donate(&mut app, &sender, &coins(10, ATOM))
+            .unwrap();
+    }
+    let resp = contract.query_badges(&app, &sender).unwrap();
+    assert_eq!(resp.badges.len(), 3);
+    assert!(resp
+        .badges
+        .iter()
+        .any(|badge| badge.badge == Badge::TenDonations));
+
+    // A failing mint lets the donation through and leaves the badge to a later donation
+    let init_msg = cw721_base::InstantiateMsg {
+        minter: owner.to_string(),
+        ..init_msg
+    };
+    let foreign = app
+        .instantiate_contract(badges_id, owner.clone(), &init_msg, &[], "Badges", None)
+        .unwrap();
+    contract
+        .update_badge_collection(&mut app, &owner, Some(&foreign))
+        .unwrap();
+    contract.donate(&mut app, &late, &coins(10, ATOM)).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 11);
+    assert!(contract
+        .query_badges(&app, &late)
+        .unwrap()
+        .badges
+        .is_empty());
+
+    contract
+        .update_badge_collection(&mut app, &owner, Some(&collection))
+        .unwrap();
+    contract.donate(&mut app, &late, &coins(10, ATOM)).unwrap();
+    let resp = contract.query_badges(&app, &late).unwrap();
+    assert_eq!(resp.badges[0].badge, Badge::FirstDonation);
+}
+
+#[test]
+fn access_lists() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &bob, &carol] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(30, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .update_access_mode(&mut app, &alice, AccessMode::Allowlist)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::ConfigManager
+        }
+    );
+    contract
+        .update_access_mode(&mut app, &owner, AccessMode::Allowlist)
+        .unwrap();
+    contract
+        .update_access_list(
+            &mut app,
+            &owner,
+            AccessMode::Allowlist,
+            &[&alice, &bob, &carol],
+            &[&carol],
+        )
+        .unwrap();
+
+    contract.donate(&mut app, &alice, &coins(10, ATOM)).unwrap();
+    let err = contract
+        .donate(&mut app, &carol, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            donor: carol.to_string()
+        }
+    );
+    let err = contract
+        .simulate_donate(&app, &carol, &coins(10, ATOM))
+        .unwrap_err();
+    assert!(err.to_string().contains("carol is not allowed to donate"));
+
+    let resp = contract.query_allowlist(&app, None, 1).unwrap();
+    assert_eq!(resp.addresses, vec![alice.clone()]);
+    let resp = contract.query_allowlist(&app, Some(&alice), None).unwrap();
+    assert_eq!(resp.addresses, vec![bob.clone()]);
+
+    contract
+        .update_access_mode(&mut app, &owner, AccessMode::Denylist)
+        .unwrap();
+    contract
+        .update_access_list(&mut app, &owner, AccessMode::Denylist, &[&alice], &[])
+        .unwrap();
+    contract.donate(&mut app, &carol, &coins(10, ATOM)).unwrap();
+    let err = contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            donor: alice.to_string()
+        }
+    );
+}
+
+fn merkle_hash(first: &[u8], second: &[u8]) -> [u8; 32] {
+    let (first, second) = if first <= second {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+#[test]
+fn merkle_eligibility() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &dave] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(30, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let leaf = |addr: &Addr| -> [u8; 32] { Sha256::digest(addr.as_bytes()).into() };
+    let root = merkle_hash(&merkle_hash(&leaf(&alice), &leaf(&bob)), &leaf(&carol));
+    let proof = [hex::encode(leaf(&bob)), hex::encode(leaf(&carol))];
+    let proof: Vec<_> = proof.iter().map(String::as_str).collect();
+
+    let err = contract
+        .update_merkle_root(&mut app, &owner, Some("not hex"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidHash {
+            hash: "not hex".to_owned()
+        }
+    );
+    contract
+        .update_merkle_root(&mut app, &owner, Some(&hex::encode(root)))
+        .unwrap();
+
+    let err = contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEligible {
+            donor: alice.to_string()
+        }
+    );
+    let err = contract
+        .donate_with_proof(&mut app, &dave, &coins(10, ATOM), &proof)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEligible {
+            donor: dave.to_string()
+        }
+    );
+
+    contract
+        .donate_with_proof(&mut app, &alice, &coins(10, ATOM), &proof)
+        .unwrap();
+    // Verified donors are cached and can donate without the proof
+    contract.donate(&mut app, &alice, &coins(10, ATOM)).unwrap();
+
+    contract.update_merkle_root(&mut app, &owner, None).unwrap();
+    contract.donate(&mut app, &dave, &coins(10, ATOM)).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 3);
+}
+
+#[test]
+fn group_membership() {
+    let owner = Addr::unchecked("owner");
+    let member = Addr::unchecked("member");
+    let light = Addr::unchecked("light");
+    let outsider = Addr::unchecked("outsider");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&member, &light, &outsider] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(10, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let group_id = app.store_code(group_contract());
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+    let members = vec![
+        Member {
+            addr: member.to_string(),
+            weight: 5,
+        },
+        Member {
+            addr: light.to_string(),
+            weight: 1,
+        },
+    ];
+    let group = app
+        .instantiate_contract(group_id, owner.clone(), &members, &[], "Group", None)
+        .unwrap();
+
+    let msg = ExecMsg::UpdateGroup {
+        group: Some(Group {
+            addr: group.to_string(),
+            min_weight: 2,
+            grant_roles: true,
+        }),
+    };
+    let err = app
+        .execute_contract(member.clone(), contract.addr().clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MissingRole { role: Role::Admin }
+    );
+    app.execute_contract(owner.clone(), contract.addr().clone(), &msg, &[])
+        .unwrap();
+
+    contract
+        .donate(&mut app, &member, &coins(10, ATOM))
+        .unwrap();
+    for addr in [&light, &outsider] {
+        let err = contract
+            .donate(&mut app, addr, &coins(10, ATOM))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotGroupMember {
+                addr: addr.to_string()
+            }
+        );
+    }
+
+    // Members meeting the weight also hold the withdrawer and resetter roles
+    contract.withdraw(&mut app, &member).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    let err = contract.reset(&mut app, &outsider).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingRole {
+            role: Role::Resetter
+        }
+    );
+    contract.reset(&mut app, &member).unwrap();
+}
+
+#[test]
+fn rate_limits() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+    contract
+        .update_rate_limit(
+            &mut app,
+            &owner,
+            Some(RateLimit {
+                cooldown: Some(Duration::Height(2)),
+                epoch: Some(Epoch {
+                    length: Duration::Time(100),
+                    max_counted: 2,
+                }),
+                max_contribution: Some(Uint128::new(35)),
+            }),
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    let height = app.block_info().height;
+    let err = contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap_err();
     assert_eq!(
-        app.wrap().query_all_balances(contract.addr()).unwrap(),
-        coins(18, ATOM)
+        err,
+        ContractError::DonationCooldown {
+            until: Expiration::AtHeight(height + 2)
+        }
+    );
+
+    let resp = contract.query_donor(&app, &sender).unwrap();
+    assert_eq!(resp.cooldown_ends, Some(Expiration::AtHeight(height + 2)));
+    assert_eq!(resp.epoch_remaining, Some(1));
+    assert_eq!(resp.contribution_remaining, Some(Uint128::new(25)));
+
+    app.update_block(|block| {
+        block.height += 2;
+        block.time = block.time.plus_seconds(10);
+    });
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| {
+        block.height += 2;
+        block.time = block.time.plus_seconds(10);
+    });
+    let err = contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::EpochLimitReached { max: 2, .. }
+    ));
+
+    app.update_block(|block| {
+        block.height += 2;
+        block.time = block.time.plus_seconds(100);
+    });
+    let err = contract
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ContributionCapExceeded {
+            cap: coin(35, ATOM)
+        }
+    );
+    contract
+        .donate(&mut app, &sender, &coins(15, ATOM))
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 3);
+}
+
+#[test]
+fn donate_for() {
+    let owner = Addr::unchecked("owner");
+    let custodian = Addr::unchecked("custodian");
+    let alice = Addr::unchecked("alice");
+    let mallory = Addr::unchecked("mallory");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &custodian, coins(40, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &alice, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &mallory, coins(10, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    contract
+        .update_access_mode(&mut app, &owner, AccessMode::Allowlist)
+        .unwrap();
+    contract
+        .update_access_list(
+            &mut app,
+            &owner,
+            AccessMode::Allowlist,
+            &[&alice, &custodian],
+            &[],
+        )
+        .unwrap();
+    contract
+        .update_rate_limit(
+            &mut app,
+            &owner,
+            Some(RateLimit {
+                cooldown: Some(Duration::Height(2)),
+                epoch: None,
+                max_contribution: Some(Uint128::new(25)),
+            }),
+        )
+        .unwrap();
+
+    // Both the funding sender and the beneficiary have to be eligible
+    let err = contract
+        .donate_for(&mut app, &mallory, &alice, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            donor: mallory.to_string()
+        }
+    );
+    let err = contract
+        .donate_for(&mut app, &custodian, &mallory, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            donor: mallory.to_string()
+        }
+    );
+    contract
+        .donate_for(&mut app, &custodian, &alice, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = contract.query_donation(&app, 0).unwrap();
+    assert_eq!(resp.donor, alice);
+    assert_eq!(resp.funded_by, Some(custodian.clone()));
+
+    // Rate limits apply to the funding sender, the beneficiary can still donate on its own
+    let height = app.block_info().height;
+    let err = contract
+        .donate_for(&mut app, &custodian, &alice, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonationCooldown {
+            until: Expiration::AtHeight(height + 2)
+        }
+    );
+    let resp = contract.query_donor(&app, &alice).unwrap();
+    assert_eq!(resp.cooldown_ends, None);
+    assert_eq!(resp.contribution_remaining, Some(Uint128::new(25)));
+    contract.donate(&mut app, &alice, &coins(20, ATOM)).unwrap();
+
+    let resp = contract.query_donor(&app, &alice).unwrap();
+    assert_eq!(resp.amount, Uint128::new(30));
+    assert_eq!(resp.donations, 2);
+    let resp = contract.query_donor(&app, &custodian).unwrap();
+    assert_eq!(resp.donations, 0);
+    assert_eq!(resp.contribution_remaining, Some(Uint128::new(15)));
+
+    app.update_block(|block| block.height += 2);
+    let err = contract
+        .donate_for(&mut app, &custodian, &alice, &coins(20, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ContributionCapExceeded {
+            cap: coin(25, ATOM)
+        }
+    );
+    contract
+        .donate(&mut app, &custodian, &coins(15, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&custodian).unwrap(),
+        coins(15, ATOM)
     );
+}
+
+#[test]
+fn scheduled_donations_with_parent() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let cranker = Addr::unchecked("cranker");
+
+    let mut app = App::new(|router, _api, storage| {
+        for donor in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, donor, coins(11, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Parent contract",
+        None,
+        None,
+        coin(0, ATOM),
+        None,
+    )
+    .unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(90),
+        }),
+    )
+    .unwrap();
+
+    for donor in [&alice, &bob] {
+        contract
+            .schedule_donation(
+                &mut app,
+                donor,
+                coin(10, ATOM),
+                Duration::Height(5),
+                1,
+                1,
+                &coins(11, ATOM),
+            )
+            .unwrap();
+    }
+
+    // The second forward is sized from what the first one and the tip left over
+    contract
+        .process_scheduled(&mut app, &cranker, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 2);
     assert_eq!(
         app.wrap()
             .query_all_balances(parent_contract.addr())
             .unwrap(),
+        coins(18, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&cranker).unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(2, ATOM)
+    );
+}
+
+#[test]
+fn matching_pools() {
+    let owner = Addr::unchecked("owner");
+    let sponsor = Addr::unchecked("sponsor");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sponsor, vec![coin(60, ATOM), coin(10, "eth")])
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender, coins(30, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+    let expires = Expiration::AtHeight(app.block_info().height + 10);
+
+    let err = contract
+        .fund_matching_pool(
+            &mut app,
+            &sponsor,
+            Decimal::one(),
+            15,
+            expires,
+            &coins(10, "eth"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::MissingDenom(ATOM.to_owned()))
+    );
+    let err = contract
+        .fund_matching_pool(
+            &mut app,
+            &sponsor,
+            Decimal::one(),
+            15,
+            expires,
+            &coins(5, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PoolTooSmall {
+            min: coin(10, ATOM)
+        }
+    );
+    for ratio in [Decimal::zero(), Decimal::from_ratio(11u128, 1u128)] {
+        let err = contract
+            .fund_matching_pool(&mut app, &sponsor, ratio, 15, expires, &coins(50, ATOM))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidMatchRatio {
+                max: Decimal::from_ratio(10u128, 1u128)
+            }
+        );
+    }
+    for expires in [
+        Expiration::Never {},
+        Expiration::AtHeight(app.block_info().height),
+    ] {
+        let err = contract
+            .fund_matching_pool(
+                &mut app,
+                &sponsor,
+                Decimal::one(),
+                15,
+                expires,
+                &coins(50, ATOM),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPoolExpiration { expires });
+    }
+    contract
+        .fund_matching_pool(
+            &mut app,
+            &sponsor,
+            Decimal::one(),
+            15,
+            expires,
+            &coins(50, ATOM),
+        )
+        .unwrap();
+    contract
+        .fund_matching_pool(
+            &mut app,
+            &sponsor,
+            Decimal::one(),
+            15,
+            expires,
+            &coins(10, ATOM),
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap();
+    // Uncounted donations are not matched
+    contract.donate(&mut app, &sender, &coins(5, ATOM)).unwrap();
+
+    let resp = contract.query_donation(&app, 0).unwrap();
+    assert_eq!(
+        resp.matches,
+        [
+            PoolMatch {
+                pool_id: 0,
+                amount: coin(15, ATOM)
+            },
+            PoolMatch {
+                pool_id: 1,
+                amount: coin(10, ATOM)
+            }
+        ]
+    );
+    assert!(contract.query_donation(&app, 1).unwrap().matches.is_empty());
+    assert_eq!(contract.query_stats(&app).unwrap().matched, coins(25, ATOM));
+
+    // The unused pool funds stay reserved for matching
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(50, ATOM)
+    );
+
+    let err = contract
+        .reclaim_matching_pool(&mut app, &sender, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotSponsor { id: 0 });
+    let err = contract
+        .reclaim_matching_pool(&mut app, &sponsor, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::PoolNotExpired { id: 0, expires });
+
+    app.update_block(|block| block.height += 10);
+    contract
+        .reclaim_matching_pool(&mut app, &sponsor, 0)
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&sponsor).unwrap(),
+        vec![coin(35, ATOM), coin(10, "eth")]
+    );
+}
+
+#[test]
+fn scheduled_donations() {
+    let owner = Addr::unchecked("owner");
+    let donor = Addr::unchecked("donor");
+    let cranker = Addr::unchecked("cranker");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &donor, coins(60, ATOM))
+            .unwrap();
+    });
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Counting contract",
+        None,
+        None,
+        coin(10, ATOM),
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .schedule_donation(
+            &mut app,
+            &donor,
+            coin(0, ATOM),
+            Duration::Height(5),
+            3,
+            2,
+            &coins(6, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::EmptySchedule);
+    let err = contract
+        .schedule_donation(
+            &mut app,
+            &donor,
+            coin(u128::MAX, ATOM),
+            Duration::Height(5),
+            2,
+            0,
+            &coins(10, ATOM),
+        )
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+    let err = contract
+        .schedule_donation(
+            &mut app,
+            &donor,
+            coin(10, ATOM),
+            Duration::Height(5),
+            3,
+            2,
+            &coins(30, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidScheduleFunds {
+            expected: coin(36, ATOM)
+        }
+    );
+    contract
+        .schedule_donation(
+            &mut app,
+            &donor,
+            coin(10, ATOM),
+            Duration::Height(5),
+            3,
+            2,
+            &coins(36, ATOM),
+        )
+        .unwrap();
+
+    // The first donation is due right away
+    contract
+        .process_scheduled(&mut app, &cranker, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        app.wrap().query_all_balances(&cranker).unwrap(),
         coins(2, ATOM)
     );
+    let resp = contract.query_donation(&app, 0).unwrap();
+    assert_eq!(resp.donor, donor);
+    assert_eq!(resp.funded_by, None);
+
+    // Nothing is due until the interval passes
+    contract
+        .process_scheduled(&mut app, &cranker, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+
+    // Prefunded donations stay reserved until processed
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+
+    app.update_block(|block| block.height += 5);
+    contract
+        .process_scheduled(&mut app, &cranker, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 2);
+    assert_eq!(
+        app.wrap().query_all_balances(&cranker).unwrap(),
+        coins(4, ATOM)
+    );
+    let schedules = contract.query_schedules(&app).unwrap().schedules;
+    assert_eq!(schedules[0].remaining, 1);
+    assert_eq!(
+        schedules[0].next,
+        Expiration::AtHeight(app.block_info().height + 5)
+    );
+
+    let err = contract.cancel_schedule(&mut app, &cranker, 0).unwrap_err();
+    assert_eq!(err, ContractError::NotScheduleDonor { id: 0 });
+    contract.cancel_schedule(&mut app, &donor, 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&donor).unwrap(),
+        coins(36, ATOM)
+    );
+    assert!(contract.query_schedules(&app).unwrap().schedules.is_empty());
+
+    // Every schedule looked at counts towards the limit, the next call resumes after it
+    for _ in 0..2 {
+        contract
+            .schedule_donation(
+                &mut app,
+                &donor,
+                coin(10, ATOM),
+                Duration::Height(5),
+                1,
+                0,
+                &coins(10, ATOM),
+            )
+            .unwrap();
+    }
+    contract.process_scheduled(&mut app, &cranker, 1).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 3);
+    let schedules = contract.query_schedules(&app).unwrap().schedules;
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].id, 2);
+    contract.process_scheduled(&mut app, &cranker, 1).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 4);
+    assert!(contract.query_schedules(&app).unwrap().schedules.is_empty());
 }
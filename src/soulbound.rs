@@ -0,0 +1,47 @@
+//! Non-transferable supporter badge collection: cw721-base with `TransferNft` and `SendNft`
+//! disabled. Deployed as a contract of its own, built with the `badges` feature, it is the only
+//! kind of collection accepted by `UpdateBadgeCollection`, recognised by the cw2 name it reports.
+#[cfg(all(feature = "badges", not(feature = "library")))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use cw721_base::{entry, Extension, InstantiateMsg, QueryMsg};
+
+use crate::error::ContractError;
+
+pub const CONTRACT_NAME: &str = concat!(env!("CARGO_PKG_NAME"), "_badges");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension, Empty>;
+
+#[cfg_attr(all(feature = "badges", not(feature = "library")), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let resp = entry::instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(resp)
+}
+
+#[cfg_attr(all(feature = "badges", not(feature = "library")), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::TransferNft { token_id, .. } | ExecuteMsg::SendNft { token_id, .. } => {
+            Err(ContractError::BadgeNotTransferable { token_id })
+        }
+        msg => Ok(entry::execute(deps, env, info, msg)?),
+    }
+}
+
+#[cfg_attr(all(feature = "badges", not(feature = "library")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg<Empty>) -> StdResult<Binary> {
+    entry::query(deps, env, msg)
+}
@@ -1,10 +1,17 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
-use cw_storage_plus::Item;
+use crate::msg::{
+    AccessMode, Badge, ExecMsg, MilestoneAction, PoolMatch, RateLimit, RewardRate, Role,
+    VestingSchedule,
+};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Empty, StdError, StdResult, Timestamp, Uint128};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Key, KeyDeserialize, Map, MultiIndex, Prefixer, PrimaryKey,
+    SnapshotItem, Strategy,
+};
+use cw_utils::{Duration, Expiration};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct State {
-    pub counter: u64,
     pub minimal_donation: Coin,
     pub owner: Addr,
     pub donating_parent: Option<u64>,
@@ -17,6 +24,299 @@ pub struct ParentDonation {
     pub part: Decimal,
 }
 
+/// Rate limiting bookkeeping of a single donor
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DonorActivity {
+    pub cooldown_ends: Option<Expiration>,
+    pub epoch_ends: Option<Expiration>,
+    /// Counted donations within the epoch ending at `epoch_ends`
+    pub epoch_counted: u64,
+    /// Campaign denom paid on behalf of others, counting towards the contribution cap
+    #[serde(default)]
+    pub donated_for_others: Uint128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemberGroup {
+    pub addr: Addr,
+    pub min_weight: u64,
+    pub grant_roles: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultisigConfig {
+    pub approvers: Vec<Addr>,
+    pub threshold: u32,
+    pub proposal_ttl: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WithdrawProposal {
+    pub proposer: Addr,
+    pub amount: Vec<Coin>,
+    pub receiver: Addr,
+    pub expires: Timestamp,
+    pub executed: bool,
+    /// Time the proposal reached the approval threshold
+    #[serde(default)]
+    pub approved_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueuedAction {
+    pub action: ExecMsg,
+    pub announcer: Addr,
+    pub execute_after: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaymentSplit {
+    pub beneficiaries: Vec<(Addr, Decimal)>,
+    pub residual_receiver: Addr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DonationRecord {
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub counted: bool,
+    pub height: u64,
+    pub time: Timestamp,
+    pub memo: Option<String>,
+    pub memo_hidden: bool,
+    /// Sender of the funds when donating on behalf of `donor`
+    #[serde(default)]
+    pub funded_by: Option<Addr>,
+    #[serde(default)]
+    pub matches: Vec<PoolMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Schedule {
+    pub donor: Addr,
+    pub amount: Coin,
+    pub tip: Uint128,
+    pub interval: Duration,
+    pub remaining: u32,
+    pub next: Expiration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchingPool {
+    pub sponsor: Addr,
+    pub ratio: Decimal,
+    pub cap: Uint128,
+    pub expires: Expiration,
+    pub remaining: Coin,
+    pub matched: Uint128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Milestone {
+    pub action: MilestoneAction,
+    pub fired_height: Option<u64>,
+    pub fired_time: Option<Timestamp>,
+    /// Set when the milestone fired without enough available funds for its payment
+    #[serde(default)]
+    pub unfunded: bool,
+    /// Set when the message executed by the milestone failed
+    #[serde(default)]
+    pub failed: bool,
+}
+
+/// Cumulative contribution of a single donor in the campaign denom
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DonorTotal {
+    pub amount: Uint128,
+    pub donations: u64,
+    /// Donations which counted towards the counter
+    #[serde(default)]
+    pub counted: u64,
+    /// Part of `amount` paid by others donating on behalf of the donor
+    #[serde(default)]
+    pub funded_by_others: Uint128,
+}
+
+pub struct DonorIndexes<'a> {
+    pub amount: MultiIndex<'a, u128, DonorTotal, &'a Addr>,
+}
+
+impl<'a> IndexList<DonorTotal> for DonorIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DonorTotal>> + '_> {
+        let v: Vec<&dyn Index<DonorTotal>> = vec![&self.amount];
+        Box::new(v.into_iter())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LargestDonation {
+    pub id: u64,
+    pub donor: Addr,
+    pub amount: Coin,
+}
+
+/// Campaign totals, updated on every donation, withdrawal and parent forward
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Stats {
+    pub counted_donations: u64,
+    pub uncounted_donations: u64,
+    pub unique_donors: u64,
+    pub received: Vec<Coin>,
+    pub withdrawn: Vec<Coin>,
+    pub forwarded: Vec<Coin>,
+    /// Largest single donation in the campaign denom
+    pub largest_donation: Option<LargestDonation>,
+    pub parent_forwards: u64,
+    #[serde(default)]
+    pub matched: Vec<Coin>,
+    #[serde(default)]
+    pub milestone_payouts: Vec<Coin>,
+}
+
+pub struct DonationIndexes<'a> {
+    /// Donations of a single donor ordered by time
+    pub donor: MultiIndex<'a, (Addr, u64), DonationRecord, u64>,
+    pub time: MultiIndex<'a, u64, DonationRecord, u64>,
+}
+
+impl<'a> IndexList<DonationRecord> for DonationIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DonationRecord>> + '_> {
+        let v: Vec<&dyn Index<DonationRecord>> = vec![&self.donor, &self.time];
+        Box::new(v.into_iter())
+    }
+}
+
 pub const STATE: Item<State> = Item::new("state");
+pub const COUNTER: SnapshotItem<u64> = SnapshotItem::new(
+    "counter_snapshot",
+    "counter_snapshot__checkpoints",
+    "counter_snapshot__changelog",
+    Strategy::EveryBlock,
+);
 pub const PARENT_DONATION: Item<ParentDonation> = Item::new("parent_donation");
+pub const ROLES: Map<(Role, &Addr), Empty> = Map::new("roles");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const MULTISIG: Item<MultisigConfig> = Item::new("multisig");
+pub const NEXT_PROPOSAL_ID: Item<u64> = Item::new("next_withdraw_proposal_id");
+pub const PROPOSALS: Map<u64, WithdrawProposal> = Map::new("withdraw_proposals");
+pub const PROPOSAL_APPROVALS: Map<(u64, &Addr), Empty> = Map::new("withdraw_approvals");
+pub const TIMELOCK_DELAY: Item<u64> = Item::new("timelock_delay");
+pub const NEXT_ACTION_ID: Item<u64> = Item::new("next_queued_action_id");
+pub const QUEUED_ACTIONS: Map<u64, QueuedAction> = Map::new("queued_actions");
+pub const VESTING: Item<VestingSchedule> = Item::new("vesting");
+pub const SPLIT: Item<PaymentSplit> = Item::new("payment_split");
+pub const STATS: Item<Stats> = Item::new("stats");
+/// Milestones keyed by the counter value firing them
+pub const MILESTONES: Map<u64, Milestone> = Map::new("milestones");
+pub const HOOKS: Map<&Addr, Empty> = Map::new("donation_hooks");
+pub const REWARD_TOKEN: Item<Addr> = Item::new("reward_token");
+pub const REWARD_RATE: Item<RewardRate> = Item::new("reward_rate");
+pub const BADGE_COLLECTION: Item<Addr> = Item::new("badge_collection");
+/// Badges already minted to each donor
+pub const BADGES: Map<(&Addr, Badge), Empty> = Map::new("badges");
+/// Badge mints waiting for their reply, keyed by the mint ID
+pub const PENDING_BADGES: Map<u64, (Addr, Badge)> = Map::new("pending_badges");
+pub const NEXT_BADGE_MINT: Item<u64> = Item::new("next_badge_mint");
+pub const ACCESS_MODE: Item<AccessMode> = Item::new("access_mode");
+pub const ALLOWLIST: Map<&Addr, Empty> = Map::new("allowlist");
+pub const DENYLIST: Map<&Addr, Empty> = Map::new("denylist");
+pub const MERKLE_ROOT: Item<Binary> = Item::new("merkle_root");
+/// Donors already verified by a proof, keyed by the root they were verified against
+pub const MERKLE_VERIFIED: Map<(&[u8], &Addr), Empty> = Map::new("merkle_verified");
+pub const GROUP: Item<MemberGroup> = Item::new("member_group");
+pub const RATE_LIMIT: Item<RateLimit> = Item::new("rate_limit");
+pub const DONOR_ACTIVITY: Map<&Addr, DonorActivity> = Map::new("donor_activity");
+/// Scheduled donations with repetitions left
+pub const SCHEDULES: Map<u64, Schedule> = Map::new("schedules");
+pub const NEXT_SCHEDULE_ID: Item<u64> = Item::new("next_schedule_id");
+/// Last schedule looked at by `ProcessScheduled`, the next call resumes after it
+pub const SCHEDULE_CURSOR: Item<u64> = Item::new("schedule_cursor");
+pub const MATCHING_POOLS: Map<u64, MatchingPool> = Map::new("matching_pools");
+/// Pools which may still match donations, dropped once emptied, expired or reclaimed
+pub const ACTIVE_POOLS: Map<u64, Empty> = Map::new("active_pools");
+pub const NEXT_POOL_ID: Item<u64> = Item::new("next_pool_id");
+/// Funds held for matching pools and scheduled donations, excluded from withdrawals and parent
+/// forwards
+pub const RESERVED: Item<Vec<Coin>> = Item::new("reserved");
+pub const NEXT_DONATION_ID: Item<u64> = Item::new("next_donation_id");
 // pub const OWNER: Item<Addr> = Item::new("owner");
+
+/// Append-only history of every donation keyed by donation ID
+pub fn donations<'a>() -> IndexedMap<'a, u64, DonationRecord, DonationIndexes<'a>> {
+    let indexes = DonationIndexes {
+        donor: MultiIndex::new(
+            |_, donation| (donation.donor.clone(), donation.time.nanos()),
+            "donations",
+            "donations__donor",
+        ),
+        time: MultiIndex::new(
+            |_, donation| donation.time.nanos(),
+            "donations",
+            "donations__time",
+        ),
+    };
+    IndexedMap::new("donations", indexes)
+}
+
+pub fn donors<'a>() -> IndexedMap<'a, &'a Addr, DonorTotal, DonorIndexes<'a>> {
+    let indexes = DonorIndexes {
+        amount: MultiIndex::new(|_, total| total.amount.u128(), "donors", "donors__amount"),
+    };
+    IndexedMap::new("donors", indexes)
+}
+
+impl<'a> PrimaryKey<'a> for Role {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Role {
+    fn prefix(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl KeyDeserialize for Role {
+    type Output = Role;
+
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str().as_bytes() == value.as_slice())
+            .ok_or_else(|| StdError::parse_err("Role", "unknown role"))
+    }
+}
+
+impl<'a> PrimaryKey<'a> for Badge {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Badge {
+    fn prefix(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl KeyDeserialize for Badge {
+    type Output = Badge;
+
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Badge::ALL
+            .into_iter()
+            .find(|badge| badge.as_str().as_bytes() == value.as_slice())
+            .ok_or_else(|| StdError::parse_err("Badge", "unknown badge"))
+    }
+}